@@ -4,7 +4,12 @@ use std::fs::create_dir_all;
 use cosmwasm_schema::{export_schema, remove_schemas, schema_for};
 
 use staking_anchor_gov::msg::{
-    ConfigResponse, ExecuteMsg, InstantiateMsg, QueryMsg, StateResponse, UserStateResponse,
+    AirdropClaimResponse, AirdropResponse, AllowlistResponse, AverageSharesResponse,
+    CompoundResponse, ConfigResponse, DepositHeadroomResponse, ExecuteMsg, FeeExemptionsResponse,
+    GovPollsResponse, HealthResponse, HookMsg, HooksResponse, InstantiateMsg, InvariantsResponse,
+    MerkleRootResponse, MetaGovConfigResponse, PositionsResponse, ProposalResponse,
+    ProposalsResponse, QueryMsg, SharesAtHeightResponse, StateResponse,
+    TotalSharesAtHeightResponse, UserStateAtHeightResponse, UserStateResponse, VestingResponse,
 };
 
 fn main() {
@@ -19,4 +24,25 @@ fn main() {
     export_schema(&schema_for!(ConfigResponse), &out_dir);
     export_schema(&schema_for!(StateResponse), &out_dir);
     export_schema(&schema_for!(UserStateResponse), &out_dir);
+    export_schema(&schema_for!(DepositHeadroomResponse), &out_dir);
+    export_schema(&schema_for!(AllowlistResponse), &out_dir);
+    export_schema(&schema_for!(FeeExemptionsResponse), &out_dir);
+    export_schema(&schema_for!(PositionsResponse), &out_dir);
+    export_schema(&schema_for!(VestingResponse), &out_dir);
+    export_schema(&schema_for!(MetaGovConfigResponse), &out_dir);
+    export_schema(&schema_for!(ProposalResponse), &out_dir);
+    export_schema(&schema_for!(ProposalsResponse), &out_dir);
+    export_schema(&schema_for!(GovPollsResponse), &out_dir);
+    export_schema(&schema_for!(HealthResponse), &out_dir);
+    export_schema(&schema_for!(InvariantsResponse), &out_dir);
+    export_schema(&schema_for!(HooksResponse), &out_dir);
+    export_schema(&schema_for!(HookMsg), &out_dir);
+    export_schema(&schema_for!(UserStateAtHeightResponse), &out_dir);
+    export_schema(&schema_for!(TotalSharesAtHeightResponse), &out_dir);
+    export_schema(&schema_for!(AverageSharesResponse), &out_dir);
+    export_schema(&schema_for!(SharesAtHeightResponse), &out_dir);
+    export_schema(&schema_for!(MerkleRootResponse), &out_dir);
+    export_schema(&schema_for!(AirdropResponse), &out_dir);
+    export_schema(&schema_for!(AirdropClaimResponse), &out_dir);
+    export_schema(&schema_for!(CompoundResponse), &out_dir);
 }
@@ -0,0 +1,192 @@
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+use cosmwasm_std::{
+    to_binary, Addr, Api, Binary, CosmosMsg, Deps, DepsMut, Env, MessageInfo, Reply, Response,
+    StdError, SubMsg, WasmMsg,
+};
+
+use crate::error::ContractError;
+use crate::msg::{
+    ConfigResponse, ExecuteMsg, InstantiateMsg, QueryMsg, VaultResponse, VaultsResponse,
+    INSTANTIATE_REPLY_ID,
+};
+use crate::state::{
+    config_read, config_store, pending_token_read, pending_token_store, read_vaults, vaults_read,
+    vaults_store, Config,
+};
+use staking_anchor_gov_msg::InstantiateMsg as VaultInstantiateMsg;
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response, ContractError> {
+    let config = Config {
+        owner: deps.api.addr_canonicalize(info.sender.as_str())?,
+        vault_code_id: msg.vault_code_id,
+    };
+    config_store(deps.storage).save(&config)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "instantiate")
+        .add_attribute("owner", info.sender)
+        .add_attribute("vault_code_id", msg.vault_code_id.to_string()))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    _env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    match msg {
+        ExecuteMsg::UpdateVaultCodeId { vault_code_id } => {
+            update_vault_code_id(deps, info, vault_code_id)
+        }
+        ExecuteMsg::CreateVault { vault, label } => create_vault(deps, info, vault, label),
+    }
+}
+
+fn assert_owner(api: &dyn Api, config: &Config, sender: &Addr) -> Result<(), ContractError> {
+    if config.owner != api.addr_canonicalize(sender.as_str())? {
+        return Err(ContractError::Unauthorized {});
+    }
+    Ok(())
+}
+
+pub fn update_vault_code_id(
+    deps: DepsMut,
+    info: MessageInfo,
+    vault_code_id: u64,
+) -> Result<Response, ContractError> {
+    let mut config: Config = config_read(deps.storage).load()?;
+    assert_owner(deps.api, &config, &info.sender)?;
+    config.vault_code_id = vault_code_id;
+    config_store(deps.storage).save(&config)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "update_vault_code_id")
+        .add_attribute("vault_code_id", vault_code_id.to_string()))
+}
+
+/// create_vault instantiates a vault, its address is registered in reply.
+pub fn create_vault(
+    deps: DepsMut,
+    info: MessageInfo,
+    mut vault: VaultInstantiateMsg,
+    label: String,
+) -> Result<Response, ContractError> {
+    let config: Config = config_read(deps.storage).load()?;
+    assert_owner(deps.api, &config, &info.sender)?;
+    let token_raw = deps.api.addr_canonicalize(vault.anchor_token.as_str())?;
+    if vaults_read(deps.storage)
+        .may_load(token_raw.as_slice())?
+        .is_some()
+    {
+        return Err(ContractError::VaultExists {});
+    }
+    pending_token_store(deps.storage).save(&token_raw)?;
+    if vault.owner.is_none() {
+        vault.owner = Some(info.sender.to_string());
+    }
+    let token = vault.anchor_token.clone();
+
+    Ok(Response::new()
+        .add_submessage(SubMsg::reply_on_success(
+            CosmosMsg::Wasm(WasmMsg::Instantiate {
+                admin: None,
+                code_id: config.vault_code_id,
+                msg: to_binary(&vault)?,
+                funds: vec![],
+                label,
+            }),
+            INSTANTIATE_REPLY_ID,
+        ))
+        .add_attribute("method", "create_vault")
+        .add_attribute("token", token))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
+    if msg.id == INSTANTIATE_REPLY_ID {
+        let token_raw = pending_token_read(deps.storage).load()?;
+        let vault = parse_contract_address(msg)?;
+        let vault_raw = deps.api.addr_canonicalize(vault.as_str())?;
+        vaults_store(deps.storage).save(token_raw.as_slice(), &vault_raw)?;
+        pending_token_store(deps.storage).remove();
+        return Ok(Response::new()
+            .add_attribute("action", "register_vault")
+            .add_attribute("token", deps.api.addr_humanize(&token_raw)?)
+            .add_attribute("vault", vault));
+    }
+
+    Err(ContractError::Std(StdError::generic_err(
+        "not supported reply",
+    )))
+}
+
+// wasmd emits `instantiate` with `_contract_address`, terra emits
+// `instantiate_contract` with `contract_address`.
+fn parse_contract_address(msg: Reply) -> Result<String, ContractError> {
+    let res = msg.result.into_result().map_err(StdError::generic_err)?;
+    res.events
+        .iter()
+        .filter(|x| x.ty == "instantiate" || x.ty == "instantiate_contract")
+        .flat_map(|x| x.attributes.iter())
+        .find(|x| x.key == "_contract_address" || x.key == "contract_address")
+        .map(|x| x.value.clone())
+        .ok_or(ContractError::ContractAddressNotFound {})
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> Result<Binary, ContractError> {
+    match msg {
+        QueryMsg::Config {} => Ok(to_binary(&query_config(deps)?)?),
+        QueryMsg::Vault { token } => Ok(to_binary(&query_vault(deps, token)?)?),
+        QueryMsg::Vaults { start_after, limit } => {
+            Ok(to_binary(&query_vaults(deps, start_after, limit)?)?)
+        }
+    }
+}
+
+fn query_config(deps: Deps) -> Result<ConfigResponse, ContractError> {
+    let config: Config = config_read(deps.storage).load()?;
+    Ok(ConfigResponse {
+        owner: deps.api.addr_humanize(&config.owner)?.to_string(),
+        vault_code_id: config.vault_code_id,
+    })
+}
+
+fn query_vault(deps: Deps, token: String) -> Result<VaultResponse, ContractError> {
+    let token_raw = deps.api.addr_canonicalize(token.as_str())?;
+    let vault_raw = vaults_read(deps.storage).load(token_raw.as_slice())?;
+    Ok(VaultResponse {
+        token,
+        vault: deps.api.addr_humanize(&vault_raw)?.to_string(),
+    })
+}
+
+fn query_vaults(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> Result<VaultsResponse, ContractError> {
+    let start_after = if let Some(x) = start_after {
+        Some(deps.api.addr_canonicalize(&x)?)
+    } else {
+        None
+    };
+    let vaults = read_vaults(deps.storage, start_after, limit)?
+        .iter()
+        .map(|(token, vault)| {
+            Ok(VaultResponse {
+                token: deps.api.addr_humanize(token)?.to_string(),
+                vault: deps.api.addr_humanize(vault)?.to_string(),
+            })
+        })
+        .collect::<Result<Vec<_>, ContractError>>()?;
+    Ok(VaultsResponse { vaults })
+}
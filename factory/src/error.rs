@@ -0,0 +1,17 @@
+use cosmwasm_std::StdError;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("Unauthorized")]
+    Unauthorized {},
+
+    #[error("Vault of the token already exists")]
+    VaultExists {},
+
+    #[error("Contract address not found in instantiate events")]
+    ContractAddressNotFound {},
+}
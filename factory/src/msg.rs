@@ -0,0 +1,58 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use staking_anchor_gov_msg::InstantiateMsg as VaultInstantiateMsg;
+
+pub const INSTANTIATE_REPLY_ID: u64 = 1;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct InstantiateMsg {
+    pub vault_code_id: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecuteMsg {
+    UpdateVaultCodeId {
+        vault_code_id: u64,
+    },
+    /// CreateVault instantiates a vault of `vault.anchor_token`,
+    /// the vault is owned by the factory owner unless `vault.owner` is set.
+    CreateVault {
+        vault: VaultInstantiateMsg,
+        label: String,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryMsg {
+    Config {},
+    Vault {
+        token: String,
+    },
+    Vaults {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct ConfigResponse {
+    pub owner: String,
+    pub vault_code_id: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct VaultResponse {
+    pub token: String,
+    pub vault: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct VaultsResponse {
+    pub vaults: Vec<VaultResponse>,
+}
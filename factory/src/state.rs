@@ -0,0 +1,74 @@
+use cosmwasm_std::{CanonicalAddr, Order, StdResult, Storage};
+use cosmwasm_storage::{
+    bucket, bucket_read, singleton, singleton_read, Bucket, ReadonlyBucket, ReadonlySingleton,
+    Singleton,
+};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+pub static KEY_CONFIG: &[u8] = b"config";
+pub static KEY_PENDING_TOKEN: &[u8] = b"pending_token";
+pub static KEY_VAULTS: &[u8] = b"vaults";
+
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Config {
+    pub owner: CanonicalAddr,
+    pub vault_code_id: u64,
+}
+
+pub fn config_store(storage: &mut dyn Storage) -> Singleton<'_, Config> {
+    singleton(storage, KEY_CONFIG)
+}
+
+pub fn config_read(storage: &dyn Storage) -> ReadonlySingleton<'_, Config> {
+    singleton_read(storage, KEY_CONFIG)
+}
+
+/// pending_token is the token of the vault being instantiated, until reply.
+pub fn pending_token_store(storage: &mut dyn Storage) -> Singleton<'_, CanonicalAddr> {
+    singleton(storage, KEY_PENDING_TOKEN)
+}
+
+pub fn pending_token_read(storage: &dyn Storage) -> ReadonlySingleton<'_, CanonicalAddr> {
+    singleton_read(storage, KEY_PENDING_TOKEN)
+}
+
+/// vaults maps the token to its vault.
+pub fn vaults_store(storage: &mut dyn Storage) -> Bucket<'_, CanonicalAddr> {
+    bucket(storage, KEY_VAULTS)
+}
+
+pub fn vaults_read(storage: &dyn Storage) -> ReadonlyBucket<'_, CanonicalAddr> {
+    bucket_read(storage, KEY_VAULTS)
+}
+
+pub fn read_vaults(
+    storage: &dyn Storage,
+    start_after: Option<CanonicalAddr>,
+    limit: Option<u32>,
+) -> StdResult<Vec<(CanonicalAddr, CanonicalAddr)>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = calc_range_start(start_after);
+
+    vaults_read(storage)
+        .range(start.as_deref(), None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (k, v) = item?;
+            Ok((CanonicalAddr::from(k), v))
+        })
+        .collect()
+}
+
+// this will set the first key after the provided key, by appending a 1 byte
+fn calc_range_start(start_after: Option<CanonicalAddr>) -> Option<Vec<u8>> {
+    start_after.map(|addr| {
+        let mut v = addr.as_slice().to_vec();
+        v.push(1);
+        v
+    })
+}
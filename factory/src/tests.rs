@@ -0,0 +1,189 @@
+use crate::contract::{execute, instantiate, query, reply};
+use crate::error::ContractError;
+use crate::msg::{
+    ConfigResponse, ExecuteMsg, InstantiateMsg, QueryMsg, VaultResponse, VaultsResponse,
+    INSTANTIATE_REPLY_ID,
+};
+
+use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
+use cosmwasm_std::{
+    from_binary, to_binary, ContractResult, CosmosMsg, Decimal, DepsMut, Event, Reply, SubMsg,
+    SubMsgExecutionResponse, WasmMsg,
+};
+use staking_anchor_gov_msg::InstantiateMsg as VaultInstantiateMsg;
+
+const TEST_CREATOR: &str = "creator";
+const TEST_ALICE: &str = "alice";
+const TEST_DEV: &str = "dev";
+const TEST_ANCHOR_TOKEN: &str = "anchor_token";
+const TEST_ANCHOR_GOV: &str = "anchor_gov";
+const TEST_MIRROR_TOKEN: &str = "mirror_token";
+const TEST_MIRROR_GOV: &str = "mirror_gov";
+const TEST_VAULT_CODE_ID: u64 = 10;
+
+fn mock_instantiate(deps: DepsMut) {
+    let msg = InstantiateMsg {
+        vault_code_id: TEST_VAULT_CODE_ID,
+    };
+    let info = mock_info(TEST_CREATOR, &[]);
+    instantiate(deps, mock_env(), info, msg).expect("contract successfully handles InstantiateMsg");
+}
+
+fn mock_vault(token: &str, gov: &str) -> VaultInstantiateMsg {
+    VaultInstantiateMsg {
+        feerate: Decimal::percent(2),
+        anchor_gov: gov.to_string(),
+        anchor_token: token.to_string(),
+        dev: TEST_DEV.to_string(),
+        gov_kind: None,
+        owner: None,
+    }
+}
+
+fn mock_instantiate_reply(ty: &str, key: &str, vault: &str) -> Reply {
+    Reply {
+        id: INSTANTIATE_REPLY_ID,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![Event::new(ty).add_attribute(key, vault)],
+            data: None,
+        }),
+    }
+}
+
+#[test]
+fn proper_initialization() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config_response: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        config_response,
+        ConfigResponse {
+            owner: TEST_CREATOR.to_string(),
+            vault_code_id: TEST_VAULT_CODE_ID,
+        }
+    );
+
+    let msg = ExecuteMsg::UpdateVaultCodeId { vault_code_id: 11 };
+    let info = mock_info(TEST_ALICE, &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
+    match res {
+        Err(ContractError::Unauthorized {}) => {}
+        _ => panic!("Must return Unauthorized error"),
+    }
+    let info = mock_info(TEST_CREATOR, &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config_response: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(config_response.vault_code_id, 11);
+}
+
+#[test]
+fn proper_create_vault() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    let msg = ExecuteMsg::CreateVault {
+        vault: mock_vault(TEST_ANCHOR_TOKEN, TEST_ANCHOR_GOV),
+        label: "anc vault".to_string(),
+    };
+    let info = mock_info(TEST_ALICE, &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
+    match res {
+        Err(ContractError::Unauthorized {}) => {}
+        _ => panic!("Must return Unauthorized error"),
+    }
+
+    // the vault is owned by the factory owner
+    let info = mock_info(TEST_CREATOR, &[]);
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
+    let mut vault = mock_vault(TEST_ANCHOR_TOKEN, TEST_ANCHOR_GOV);
+    vault.owner = Some(TEST_CREATOR.to_string());
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::reply_on_success(
+            CosmosMsg::Wasm(WasmMsg::Instantiate {
+                admin: None,
+                code_id: TEST_VAULT_CODE_ID,
+                msg: to_binary(&vault).unwrap(),
+                funds: vec![],
+                label: "anc vault".to_string(),
+            }),
+            INSTANTIATE_REPLY_ID
+        )]
+    );
+
+    let reply_msg = mock_instantiate_reply("instantiate", "code_id", "10");
+    let res = reply(deps.as_mut(), mock_env(), reply_msg);
+    match res {
+        Err(ContractError::ContractAddressNotFound {}) => {}
+        _ => panic!("Must return ContractAddressNotFound error"),
+    }
+    let reply_msg = mock_instantiate_reply("instantiate", "_contract_address", "anc_vault");
+    reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+    let query_msg = QueryMsg::Vault {
+        token: TEST_ANCHOR_TOKEN.to_string(),
+    };
+    let res = query(deps.as_ref(), mock_env(), query_msg).unwrap();
+    let vault_response: VaultResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        vault_response,
+        VaultResponse {
+            token: TEST_ANCHOR_TOKEN.to_string(),
+            vault: "anc_vault".to_string(),
+        }
+    );
+
+    // one vault per token
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::VaultExists {}) => {}
+        _ => panic!("Must return VaultExists error"),
+    }
+}
+
+#[test]
+fn query_vaults() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    let info = mock_info(TEST_CREATOR, &[]);
+    for (token, gov, vault) in [
+        (TEST_ANCHOR_TOKEN, TEST_ANCHOR_GOV, "anc_vault"),
+        (TEST_MIRROR_TOKEN, TEST_MIRROR_GOV, "mir_vault"),
+    ]
+    .iter()
+    {
+        let msg = ExecuteMsg::CreateVault {
+            vault: mock_vault(token, gov),
+            label: vault.to_string(),
+        };
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+        // terra emits the address as `contract_address` of `instantiate_contract`
+        let reply_msg = mock_instantiate_reply("instantiate_contract", "contract_address", vault);
+        reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+    }
+
+    let msg = QueryMsg::Vaults {
+        start_after: None,
+        limit: None,
+    };
+    let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+    let vaults = from_binary::<VaultsResponse>(&res).unwrap().vaults;
+    // vaults are ordered by the canonical token address
+    assert_eq!(vaults.len(), 2);
+    assert!(vaults.contains(&VaultResponse {
+        token: TEST_ANCHOR_TOKEN.to_string(),
+        vault: "anc_vault".to_string(),
+    }));
+    assert!(vaults.contains(&VaultResponse {
+        token: TEST_MIRROR_TOKEN.to_string(),
+        vault: "mir_vault".to_string(),
+    }));
+
+    let msg = QueryMsg::Vaults {
+        start_after: Some(vaults[0].token.clone()),
+        limit: Some(1),
+    };
+    let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+    let vaults_response: VaultsResponse = from_binary(&res).unwrap();
+    assert_eq!(vaults_response.vaults, vec![vaults[1].clone()]);
+}
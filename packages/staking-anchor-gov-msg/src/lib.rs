@@ -0,0 +1,25 @@
+//! Messages shared by the vault and the factory, so the factory does not link the vault contract.
+use cosmwasm_std::Decimal;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct InstantiateMsg {
+    pub feerate: Decimal,
+    pub anchor_gov: String,
+    pub anchor_token: String,
+    pub dev: String,
+    /// gov_kind picks the gov adapter, defaults to `anchor`.
+    pub gov_kind: Option<GovKind>,
+    /// owner defaults to the sender, a factory passes the real owner here.
+    pub owner: Option<String>,
+}
+
+/// GovKind is the message format of the staking gov contract.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum GovKind {
+    Anchor,
+    Mirror,
+}
@@ -1,27 +1,71 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    attr, from_binary, to_binary, Addr, Attribute, Binary, CanonicalAddr, CosmosMsg, Decimal, Deps,
-    DepsMut, Env, MessageInfo, QuerierWrapper, QueryRequest, Reply, Response, StdError, StdResult,
-    SubMsg, Uint128, WasmMsg, WasmQuery,
+    attr, from_binary, to_binary, Addr, Api, Attribute, BankMsg, Binary, CanonicalAddr, Coin,
+    CosmosMsg, Decimal, Deps, DepsMut, Env, Event, MessageInfo, Order, QuerierWrapper,
+    QueryRequest, Reply, Response, StdError, StdResult, Storage, SubMsg, Uint128, WasmMsg,
+    WasmQuery,
 };
 
 use crate::error::ContractError;
+use crate::gov::{GovAdapter, PollOutcome};
+use crate::merkle;
 use crate::msg::{
-    ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, QueryMsg, StateResponse,
-    UserStateResponse, WITHDRAW_REPLY_ID,
+    AirdropClaimResponse, AirdropResponse, AllowlistResponse, AverageSharesResponse,
+    CompoundResponse, ConfigResponse, Cw20HookMsg, DepositHeadroomResponse, ExecuteMsg,
+    FeeExemptionsResponse, FeeMode, FeeTier, GovKind, GovPollResponse, GovPollsResponse,
+    HealthResponse, HolderShares, HookInfo, HookMsg, HooksResponse, InstantiateMsg,
+    InvariantsCarry, InvariantsResponse, MerkleRootResponse, MetaGovConfigResponse,
+    PositionResponse, PositionsResponse, ProposalAction, ProposalResponse, ProposalStatus,
+    ProposalVote, ProposalsResponse, QueryMsg, ShareDelta, SharesAtHeightResponse, StateResponse,
+    StrayAncPolicy, TokenInfo, TotalSharesAtHeightResponse, UserStateAtHeightResponse,
+    UserStateResponse, VestingResponse, CREATE_POLL_REPLY_ID, CREATE_POLL_WITHDRAW_REPLY_ID,
+    HOOK_REPLY_ID, WITHDRAW_REPLY_ID,
 };
 use crate::state::{
-    config_read, config_store, feerate_read, feerate_store, temp_send_read, temp_send_store,
-    total_shares_read, total_shares_store, user_states_read, user_states_store, Config,
-    TempSendData,
+    airdrop_claims_read, airdrop_claims_store, airdrops_read, airdrops_store,
+    allowlist_enabled_read, allowlist_enabled_store, allowlist_read, allowlist_store,
+    claimable_fees_read, claimable_fees_store, compound_incentive_read, compound_incentive_store,
+    config_read, config_store, deposit_limits_read, deposit_limits_store, dev_vesting_read,
+    dev_vesting_store, exit_fee_read, exit_fee_store, fee_exemptions_read, fee_exemptions_store,
+    fee_mode_read, fee_mode_store, fee_tiers_read, fee_tiers_store, feerate_read, feerate_store,
+    gov_checkpoint_read, gov_checkpoint_store, gov_polls_read, gov_polls_store, holders_read,
+    hooks_read, hooks_store, last_compound_read, last_compound_store, last_deposit_read,
+    last_deposit_store, lock_config_read, lock_config_store, merkle_roots_read, merkle_roots_store,
+    meta_gov_config_read, meta_gov_config_store, outstanding_poll_deposits_read,
+    outstanding_poll_deposits_store, paused_read, paused_store, pool_poll_deposits_read,
+    pool_poll_deposits_store, position_count_read, position_count_store, positions_read,
+    positions_store, proposal_count_read, proposal_count_store, proposals_read, proposals_store,
+    read_addresses, read_gov_polls, read_positions, read_proposals, read_user_states,
+    save_total_shares, save_user_shares, stray_anc_policy_read, stray_anc_policy_store,
+    temp_poll_read, temp_poll_store, temp_send_read, temp_send_store, total_share_seconds,
+    total_shares_at_height, total_shares_read, total_shares_store, unclaimed_airdrops,
+    user_share_seconds, user_shares_at_height, user_states_read, vesting_read, vesting_store,
+    vote_locks_read, vote_locks_store, votes_read, votes_store, Airdrop, Compound, Config,
+    DepositLimits, ExitFee, GovCheckpoint, GovPoll, Hook, LockConfig, MerkleRoot, MetaGovConfig,
+    Position, Proposal, TempPollData, TempSendData, VestingSchedule, Vote, VoteLock,
 };
 
-use anchor_token::gov::{
-    Cw20HookMsg as GovCw20HookMsg, ExecuteMsg as GovExcuteMsg, QueryMsg as GovQueryMsg,
-    StakerResponse,
+use cw20::{
+    BalanceResponse as Cw20BalanceResponse, Cw20ExecuteMsg, Cw20QueryMsg, Cw20ReceiveMsg,
+    Expiration,
 };
-use cw20::{BalanceResponse as Cw20BalanceResponse, Cw20ExecuteMsg, Cw20QueryMsg, Cw20ReceiveMsg};
+
+// hooks are called on every deposit and withdrawal.
+const MAX_HOOKS: usize = 10;
+// a pending owner has to accept within about 30 days.
+const MAX_TRANSFER_SECONDS: u64 = 30 * 86400;
+const MAX_TRANSFER_BLOCKS: u64 = 432_000;
+const MAX_COMPOUND_INCENTIVE: u64 = 5;
+// the exit fee decays to zero within a year.
+const MAX_EXIT_FEE_DURATION: u64 = 365 * 86400;
+// dev fee shares vest within four years.
+const MAX_VESTING_DURATION: u64 = 4 * 365 * 86400;
+const MAX_LOCK_DURATION: u64 = 4 * 365 * 86400;
+// quorum is measured against un-boosted shares, a locked share counts at most twice.
+const MAX_BOOST: u64 = 100;
+// a meta-gov vote lasts at most about 30 days.
+const MAX_VOTING_PERIOD: u64 = 30 * 86400;
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
@@ -30,12 +74,17 @@ pub fn instantiate(
     info: MessageInfo,
     msg: InstantiateMsg,
 ) -> Result<Response, ContractError> {
+    let owner = msg.owner.unwrap_or_else(|| info.sender.to_string());
     let config = Config {
-        owner: deps.api.addr_canonicalize(info.sender.as_str())?,
+        owner: Some(deps.api.addr_canonicalize(owner.as_str())?),
         pendding_owner: None,
+        pendding_owner_expires: None,
         dev: deps.api.addr_canonicalize(msg.dev.as_str())?,
+        guardian: None,
+        pendding_guardian: None,
         anchor_token: deps.api.addr_canonicalize(msg.anchor_token.as_str())?,
         anchor_gov: deps.api.addr_canonicalize(msg.anchor_gov.as_str())?,
+        gov_kind: msg.gov_kind.unwrap_or(GovKind::Anchor),
     };
 
     // store value
@@ -43,12 +92,40 @@ pub fn instantiate(
 
     feerate_store(deps.storage).save(&msg.feerate)?;
 
+    fee_tiers_store(deps.storage).save(&vec![])?;
+
+    hooks_store(deps.storage).save(&vec![])?;
+
+    compound_incentive_store(deps.storage).save(&Decimal::zero())?;
+
+    fee_mode_store(deps.storage).save(&FeeMode::Shares)?;
+
+    claimable_fees_store(deps.storage).save(&Uint128::zero())?;
+
+    stray_anc_policy_store(deps.storage).save(&StrayAncPolicy::CreditPool)?;
+
+    proposal_count_store(deps.storage).save(&0u64)?;
+
+    exit_fee_store(deps.storage).save(&ExitFee::default())?;
+
+    lock_config_store(deps.storage).save(&LockConfig::default())?;
+
+    dev_vesting_store(deps.storage).save(&0u64)?;
+
+    position_count_store(deps.storage).save(&0u64)?;
+
     total_shares_store(deps.storage).save(&Uint128::zero())?;
 
+    paused_store(deps.storage).save(&false)?;
+
+    deposit_limits_store(deps.storage).save(&DepositLimits::default())?;
+
+    allowlist_enabled_store(deps.storage).save(&false)?;
+
     // add event
     Ok(Response::new()
         .add_attribute("method", "instantiate")
-        .add_attribute("owner", info.sender)
+        .add_attribute("owner", owner)
         .add_attribute("feerate", msg.feerate.to_string())
         .add_attribute("anchor_token", msg.anchor_token)
         .add_attribute("anchor_gov", msg.anchor_gov)
@@ -62,31 +139,136 @@ pub fn execute(
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
+    // no message takes native coins, they would be stuck in the contract.
+    if !info.funds.is_empty() {
+        return Err(ContractError::UnexpectedFunds {});
+    }
     match msg {
         ExecuteMsg::Receive(msg) => receive_cw20(deps, _env, info, msg),
-        ExecuteMsg::TransferOwnerShip { new_owner } => set_pedding_owner(deps, info, new_owner),
-        ExecuteMsg::AcceptOwner {} => accept_owner(deps, info),
+        ExecuteMsg::TransferOwnerShip { new_owner, expires } => {
+            set_pedding_owner(deps, _env, info, new_owner, expires)
+        }
+        ExecuteMsg::AcceptOwner {} => accept_owner(deps, _env, info),
+        ExecuteMsg::RenounceOwnership {} => renounce_ownership(deps, info),
+        ExecuteMsg::TransferGuardian { new_guardian } => {
+            set_pedding_guardian(deps, info, new_guardian)
+        }
+        ExecuteMsg::AcceptGuardian {} => accept_guardian(deps, info),
+        ExecuteMsg::RemoveGuardian {} => remove_guardian(deps, info),
+        ExecuteMsg::Pause {} => pause(deps, info),
+        ExecuteMsg::Unpause {} => unpause(deps, info),
         ExecuteMsg::UpdateDev { new_dev } => update_dev(deps, info, new_dev),
         ExecuteMsg::UpdateFeeRate { new_feerate } => update_feerate(deps, info, new_feerate),
+        ExecuteMsg::UpdateFeeTiers { tiers } => update_fee_tiers(deps, info, tiers),
+        ExecuteMsg::UpdateFeeMode { fee_mode } => update_fee_mode(deps, info, fee_mode),
+        ExecuteMsg::ClaimFees {} => claim_fees(deps, info),
+        ExecuteMsg::UpdateStrayAncPolicy { policy } => update_stray_anc_policy(deps, info, policy),
+        ExecuteMsg::RecoverToken {
+            token,
+            amount,
+            recipient,
+        } => recover_token(deps, _env, info, token, amount, recipient),
+        ExecuteMsg::SweepNative { recipient } => sweep_native(deps, _env, info, recipient),
+        ExecuteMsg::UpdateMetaGovConfig {
+            quorum,
+            threshold,
+            voting_period,
+            veto_until,
+            proposal_min_shares,
+        } => update_meta_gov_config(
+            deps,
+            info,
+            quorum,
+            threshold,
+            voting_period,
+            veto_until,
+            proposal_min_shares,
+        ),
+        ExecuteMsg::CreateProposal { action } => create_proposal(deps, _env, info, action),
+        ExecuteMsg::CastVote { proposal_id, vote } => {
+            cast_vote(deps, _env, info, proposal_id, vote)
+        }
+        ExecuteMsg::EndProposal { proposal_id } => end_proposal(deps, _env, proposal_id),
+        ExecuteMsg::VetoProposal { proposal_id } => veto_proposal(deps, _env, info, proposal_id),
+        ExecuteMsg::UpdateExitFee {
+            max_feerate,
+            duration,
+        } => update_exit_fee(deps, info, max_feerate, duration),
+        ExecuteMsg::UpdateDevVesting { duration } => update_dev_vesting(deps, info, duration),
+        ExecuteMsg::UpdateLockConfig {
+            max_lock_duration,
+            max_boost,
+        } => update_lock_config(deps, info, max_lock_duration, max_boost),
+        ExecuteMsg::UpdateDepositLimits {
+            max_total_deposit,
+            max_per_user,
+        } => update_deposit_limits(deps, info, max_total_deposit, max_per_user),
+        ExecuteMsg::SetAllowlistEnabled { enabled } => set_allowlist_enabled(deps, info, enabled),
+        ExecuteMsg::UpdateAllowlist { add, remove } => update_allowlist(deps, info, add, remove),
+        ExecuteMsg::UpdateFeeExemptions { add, remove } => {
+            update_fee_exemptions(deps, info, add, remove)
+        }
+        ExecuteMsg::UpdateHooks { add, remove } => update_hooks(deps, info, add, remove),
+        ExecuteMsg::RegisterAirdrop { token, amount } => {
+            register_airdrop(deps, _env, info, token, amount)
+        }
+        ExecuteMsg::ClaimAirdrop { token, height } => claim_airdrop(deps, info, token, height),
+        ExecuteMsg::Compound {} => compound(deps, _env, info),
+        ExecuteMsg::SettleGovPoll { poll_id } => settle_gov_poll(deps, _env, poll_id),
+        ExecuteMsg::UpdateCompoundIncentive { rate } => update_compound_incentive(deps, info, rate),
+        ExecuteMsg::CommitMerkleRoot {
+            height,
+            merkle_root,
+            replace,
+        } => commit_merkle_root(
+            deps,
+            _env,
+            info,
+            height,
+            merkle_root,
+            replace.unwrap_or(false),
+        ),
         ExecuteMsg::WithdrawToken { amount } => withdraw_token(deps, _env, info, amount),
     }
 }
 
+/// assert_owner returns Unauthorized if sender is not owner, or if ownership was renounced.
+fn assert_owner(api: &dyn Api, config: &Config, sender: &Addr) -> Result<(), ContractError> {
+    if config.owner != Some(api.addr_canonicalize(sender.as_str())?) {
+        return Err(ContractError::Unauthorized {});
+    }
+    Ok(())
+}
+
 pub fn set_pedding_owner(
     deps: DepsMut,
+    env: Env,
     info: MessageInfo,
     new_owner: String,
+    expires: Option<Expiration>,
 ) -> Result<Response, ContractError> {
     let mut config: Config = config_read(deps.storage).load()?;
     // return Unauthorized, if sender not owner
-    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
-        return Err(ContractError::Unauthorized {});
-    }
+    assert_owner(deps.api, &config, &info.sender)?;
 
     // if new_owner is empty, reset pendding owner.
     let mut new_pendding_owner: Option<CanonicalAddr> = None;
+    let mut new_pendding_owner_expires: Option<Expiration> = None;
     if !new_owner.is_empty() {
+        let expires = expires.ok_or(ContractError::InvalidExpiration {})?;
+        if expires.is_expired(&env.block) {
+            return Err(ContractError::InvalidExpiration {});
+        }
+        let too_long = match expires {
+            Expiration::AtHeight(height) => height > env.block.height + MAX_TRANSFER_BLOCKS,
+            Expiration::AtTime(time) => time > env.block.time.plus_seconds(MAX_TRANSFER_SECONDS),
+            Expiration::Never {} => true,
+        };
+        if too_long {
+            return Err(ContractError::ExpirationTooLong {});
+        }
         new_pendding_owner = Some(deps.api.addr_canonicalize(new_owner.as_str())?);
+        new_pendding_owner_expires = Some(expires);
     }
 
     let old_pendding = if let Some(x) = config.pendding_owner {
@@ -97,48 +279,196 @@ pub fn set_pedding_owner(
 
     // store config
     config.pendding_owner = new_pendding_owner;
+    config.pendding_owner_expires = new_pendding_owner_expires;
     config_store(deps.storage).save(&config)?;
 
     Ok(Response::new()
         .add_attribute("method", "set_pedding_owner")
         .add_attribute("old_pendding", old_pendding)
-        .add_attribute("new_pendding", config.owner.to_string()))
+        .add_attribute("new_pendding", new_owner)
+        .add_attribute(
+            "expires",
+            new_pendding_owner_expires
+                .map(|x| x.to_string())
+                .unwrap_or_default(),
+        ))
 }
-pub fn accept_owner(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+pub fn accept_owner(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
     let mut config: Config = config_read(deps.storage).load()?;
 
     let pedding_owner = config
         .pendding_owner
+        .clone()
         .ok_or(ContractError::Unauthorized {})?;
 
     if deps.api.addr_canonicalize(info.sender.as_str())? != pedding_owner {
         return Err(ContractError::Unauthorized {});
     }
 
-    let old_owner = config.owner;
+    if let Some(expires) = config.pendding_owner_expires {
+        if expires.is_expired(&env.block) {
+            return Err(ContractError::OwnershipTransferExpired {});
+        }
+    }
+
+    let old_owner = if let Some(x) = config.owner {
+        x.to_string()
+    } else {
+        String::default()
+    };
 
     // store config
-    config.owner = pedding_owner;
+    config.owner = Some(pedding_owner);
     config.pendding_owner = None;
+    config.pendding_owner_expires = None;
     config_store(deps.storage).save(&config)?;
 
     Ok(Response::new()
         .add_attribute("method", "ownership_transferred")
-        .add_attribute("old_owner", old_owner.to_string())
-        .add_attribute("new_owner", config.owner.to_string()))
+        .add_attribute("old_owner", old_owner)
+        .add_attribute("new_owner", info.sender))
 }
 
-pub fn update_dev(
+/// renounce_ownership drops the owner for good, the config can not be changed anymore.
+/// guardian is dropped as well, otherwise a pause could never be lifted,
+/// and so is meta governance, which could still change dev, feerate and pause.
+pub fn renounce_ownership(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    let mut config: Config = config_read(deps.storage).load()?;
+    assert_owner(deps.api, &config, &info.sender)?;
+
+    if paused_read(deps.storage).load()? {
+        return Err(ContractError::Paused {});
+    }
+
+    // store config
+    config.owner = None;
+    config.pendding_owner = None;
+    config.pendding_owner_expires = None;
+    config.guardian = None;
+    config.pendding_guardian = None;
+    config_store(deps.storage).save(&config)?;
+    meta_gov_config_store(deps.storage).remove();
+
+    Ok(Response::new()
+        .add_attribute("method", "renounce_ownership")
+        .add_attribute("old_owner", info.sender))
+}
+
+pub fn set_pedding_guardian(
     deps: DepsMut,
     info: MessageInfo,
-    new_dev: String,
+    new_guardian: String,
 ) -> Result<Response, ContractError> {
     let mut config: Config = config_read(deps.storage).load()?;
+    // only owner can rotate the guardian
+    assert_owner(deps.api, &config, &info.sender)?;
+
+    // if new_guardian is empty, reset pendding guardian.
+    let mut new_pendding_guardian: Option<CanonicalAddr> = None;
+    if !new_guardian.is_empty() {
+        new_pendding_guardian = Some(deps.api.addr_canonicalize(new_guardian.as_str())?);
+    }
+
+    let old_pendding = if let Some(x) = config.pendding_guardian {
+        x.to_string()
+    } else {
+        String::default()
+    };
+
+    // store config
+    config.pendding_guardian = new_pendding_guardian;
+    config_store(deps.storage).save(&config)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "set_pedding_guardian")
+        .add_attribute("old_pendding", old_pendding)
+        .add_attribute("new_pendding", new_guardian))
+}
+
+pub fn accept_guardian(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    let mut config: Config = config_read(deps.storage).load()?;
+
+    let pedding_guardian = config
+        .pendding_guardian
+        .ok_or(ContractError::Unauthorized {})?;
+
+    if deps.api.addr_canonicalize(info.sender.as_str())? != pedding_guardian {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let old_guardian = if let Some(x) = config.guardian {
+        x.to_string()
+    } else {
+        String::default()
+    };
+
+    // store config
+    config.guardian = Some(pedding_guardian);
+    config.pendding_guardian = None;
+    config_store(deps.storage).save(&config)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "guardian_transferred")
+        .add_attribute("old_guardian", old_guardian)
+        .add_attribute("new_guardian", info.sender))
+}
+
+/// remove_guardian lets the owner drop the guardian without a successor accepting the role.
+pub fn remove_guardian(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    let mut config: Config = config_read(deps.storage).load()?;
+    assert_owner(deps.api, &config, &info.sender)?;
+
+    let old_guardian = if let Some(x) = config.guardian {
+        x.to_string()
+    } else {
+        String::default()
+    };
+
+    // store config
+    config.guardian = None;
+    config.pendding_guardian = None;
+    config_store(deps.storage).save(&config)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "remove_guardian")
+        .add_attribute("old_guardian", old_guardian))
+}
 
-    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
+/// pause stops deposits and withdrawals, callable by guardian or owner.
+pub fn pause(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    let config: Config = config_read(deps.storage).load()?;
+    let sender_raw = Some(deps.api.addr_canonicalize(info.sender.as_str())?);
+    if sender_raw != config.owner && sender_raw != config.guardian {
         return Err(ContractError::Unauthorized {});
     }
 
+    paused_store(deps.storage).save(&true)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "pause")
+        .add_attribute("sender", info.sender))
+}
+
+/// unpause is reserved to the owner, the guardian can only pause.
+pub fn unpause(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    let config: Config = config_read(deps.storage).load()?;
+    assert_owner(deps.api, &config, &info.sender)?;
+
+    paused_store(deps.storage).save(&false)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "unpause")
+        .add_attribute("sender", info.sender))
+}
+
+pub fn update_dev(
+    deps: DepsMut,
+    info: MessageInfo,
+    new_dev: String,
+) -> Result<Response, ContractError> {
+    let mut config: Config = config_read(deps.storage).load()?;
+    assert_owner(deps.api, &config, &info.sender)?;
+
     let response = Response::new()
         .add_attribute("method", "update_dev")
         .add_attribute("old_dev", config.dev.to_string());
@@ -150,35 +480,1280 @@ pub fn update_dev(
     Ok(response.add_attribute("new_dev", new_dev))
 }
 
-pub fn update_feerate(
+pub fn update_feerate(
+    deps: DepsMut,
+    info: MessageInfo,
+    new_feerate: Decimal,
+) -> Result<Response, ContractError> {
+    let config: Config = config_read(deps.storage).load()?;
+    assert_owner(deps.api, &config, &info.sender)?;
+    let old_feerate = feerate_read(deps.storage).load()?;
+    // feerate range check, tiers must stay below the base rate.
+    let tiers = fee_tiers_read(deps.storage).load()?;
+    let new_feerate = feerate_limits(new_feerate, &tiers)?;
+
+    // store feerate to state value.
+    feerate_store(deps.storage).save(&new_feerate)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "update_feerate")
+        .add_attribute("old_feerate", old_feerate.to_string())
+        .add_attribute("new_feerate", new_feerate.to_string()))
+}
+
+pub fn update_fee_tiers(
+    deps: DepsMut,
+    info: MessageInfo,
+    tiers: Vec<FeeTier>,
+) -> Result<Response, ContractError> {
+    let config: Config = config_read(deps.storage).load()?;
+    assert_owner(deps.api, &config, &info.sender)?;
+    let feerate = feerate_read(deps.storage).load()?;
+    feerate_limits(feerate, &tiers)?;
+
+    fee_tiers_store(deps.storage).save(&tiers)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "update_fee_tiers")
+        .add_attribute("tiers", tiers.len().to_string()))
+}
+pub fn update_exit_fee(
+    deps: DepsMut,
+    info: MessageInfo,
+    max_feerate: Decimal,
+    duration: u64,
+) -> Result<Response, ContractError> {
+    let config: Config = config_read(deps.storage).load()?;
+    assert_owner(deps.api, &config, &info.sender)?;
+    let max_feerate = feerate_limits(max_feerate, &[])?;
+    if duration > MAX_EXIT_FEE_DURATION {
+        return Err(ContractError::DurationTooLong {});
+    }
+
+    exit_fee_store(deps.storage).save(&ExitFee {
+        max_feerate,
+        duration,
+    })?;
+
+    Ok(Response::new()
+        .add_attribute("method", "update_exit_fee")
+        .add_attribute("max_feerate", max_feerate.to_string())
+        .add_attribute("duration", duration.to_string()))
+}
+
+// seconds_to_free_exit returns how long a user still pays the exit fee.
+fn seconds_to_free_exit(exit_fee: &ExitFee, last_deposit: Option<u64>, now: u64) -> u64 {
+    last_deposit
+        .map(|x| x.saturating_add(exit_fee.duration).saturating_sub(now))
+        .unwrap_or_default()
+}
+
+pub fn update_fee_mode(
+    deps: DepsMut,
+    info: MessageInfo,
+    fee_mode: FeeMode,
+) -> Result<Response, ContractError> {
+    let config: Config = config_read(deps.storage).load()?;
+    assert_owner(deps.api, &config, &info.sender)?;
+
+    fee_mode_store(deps.storage).save(&fee_mode)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "update_fee_mode")
+        .add_attribute("fee_mode", format!("{:?}", fee_mode)))
+}
+
+pub fn claim_fees(deps: DepsMut, info: MessageInfo) -> Result<Response, ContractError> {
+    let config: Config = config_read(deps.storage).load()?;
+    if config.dev != deps.api.addr_canonicalize(info.sender.as_str())? {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let claimable_fees = claimable_fees_read(deps.storage).load()?;
+    if claimable_fees.is_zero() {
+        return Err(ContractError::NothingToClaim {});
+    }
+    claimable_fees_store(deps.storage).save(&Uint128::zero())?;
+
+    transfer_tokens(
+        deps,
+        &config.anchor_token,
+        &config.dev,
+        claimable_fees,
+        "claim_fees",
+    )
+}
+
+pub fn update_stray_anc_policy(
+    deps: DepsMut,
+    info: MessageInfo,
+    policy: StrayAncPolicy,
+) -> Result<Response, ContractError> {
+    let config: Config = config_read(deps.storage).load()?;
+    assert_owner(deps.api, &config, &info.sender)?;
+
+    stray_anc_policy_store(deps.storage).save(&policy)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "update_stray_anc_policy")
+        .add_attribute("policy", format!("{:?}", policy)))
+}
+
+/// recover_token rescues tokens sent to the contract by mistake.
+/// depositor principal is staked in gov, so only stray ANC beyond the claimable fees and
+/// the outstanding poll deposits is left here.
+pub fn recover_token(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    token: TokenInfo,
+    amount: Uint128,
+    recipient: String,
+) -> Result<Response, ContractError> {
+    let config: Config = config_read(deps.storage).load()?;
+    assert_owner(deps.api, &config, &info.sender)?;
+    let recipient = deps.api.addr_validate(recipient.as_str())?;
+
+    match token {
+        TokenInfo::NativeToken { denom } => Ok(Response::new()
+            .add_message(CosmosMsg::Bank(BankMsg::Send {
+                to_address: recipient.to_string(),
+                amount: vec![Coin {
+                    denom: denom.clone(),
+                    amount,
+                }],
+            }))
+            .add_attribute("method", "recover_token")
+            .add_attribute("denom", denom)
+            .add_attribute("recipient", recipient)
+            .add_attribute("amount", amount.to_string())),
+        TokenInfo::Token { contract_addr } => {
+            let token_raw = deps.api.addr_canonicalize(contract_addr.as_str())?;
+            if token_raw == config.anchor_token {
+                if stray_anc_policy_read(deps.storage).load()? == StrayAncPolicy::CreditPool {
+                    return Err(ContractError::StrayAncCredited {});
+                }
+                let balance = query_token_balance(
+                    &deps.querier,
+                    deps.api.addr_humanize(&config.anchor_token)?,
+                    env.contract.address,
+                )?;
+                let claimable_fees = claimable_fees_read(deps.storage).load()?;
+                // deposits returned by gov polls go back to the pool on settlement.
+                let outstanding = outstanding_poll_deposits_read(deps.storage)
+                    .may_load()?
+                    .unwrap_or_default();
+                if amount > balance.saturating_sub(claimable_fees + outstanding) {
+                    return Err(ContractError::InsufficientFunds {});
+                }
+            } else {
+                // unclaimed airdrops belong to the shareholders.
+                let unclaimed = unclaimed_airdrops(deps.storage, &token_raw)?;
+                if !unclaimed.is_zero() {
+                    let balance = query_token_balance(
+                        &deps.querier,
+                        deps.api.addr_validate(contract_addr.as_str())?,
+                        env.contract.address,
+                    )?;
+                    if amount > balance.checked_sub(unclaimed)? {
+                        return Err(ContractError::InsufficientFunds {});
+                    }
+                }
+            }
+            let recipient_raw = deps.api.addr_canonicalize(recipient.as_str())?;
+            transfer_tokens(deps, &token_raw, &recipient_raw, amount, "recover_token")
+        }
+    }
+}
+
+/// sweep_native sends every native coin held by the contract to recipient.
+pub fn sweep_native(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    recipient: String,
+) -> Result<Response, ContractError> {
+    let config: Config = config_read(deps.storage).load()?;
+    assert_owner(deps.api, &config, &info.sender)?;
+    let recipient = deps.api.addr_validate(recipient.as_str())?;
+
+    let balances = deps.querier.query_all_balances(env.contract.address)?;
+    if balances.is_empty() {
+        return Err(ContractError::InsufficientFunds {});
+    }
+
+    Ok(Response::new()
+        .add_message(CosmosMsg::Bank(BankMsg::Send {
+            to_address: recipient.to_string(),
+            amount: balances,
+        }))
+        .add_attribute("method", "sweep_native")
+        .add_attribute("recipient", recipient))
+}
+
+pub fn update_meta_gov_config(
+    deps: DepsMut,
+    info: MessageInfo,
+    quorum: Decimal,
+    threshold: Decimal,
+    voting_period: u64,
+    veto_until: u64,
+    proposal_min_shares: Decimal,
+) -> Result<Response, ContractError> {
+    let config: Config = config_read(deps.storage).load()?;
+    assert_owner(deps.api, &config, &info.sender)?;
+    // a single small holder must not be able to pass or spam proposals.
+    if quorum.is_zero()
+        || quorum > Decimal::one()
+        || threshold < Decimal::percent(50)
+        || threshold > Decimal::one()
+        || proposal_min_shares.is_zero()
+        || proposal_min_shares > Decimal::one()
+        || voting_period == 0
+        || voting_period > MAX_VOTING_PERIOD
+    {
+        return Err(ContractError::InvalidMetaGovConfig {});
+    }
+
+    meta_gov_config_store(deps.storage).save(&MetaGovConfig {
+        quorum,
+        threshold,
+        voting_period,
+        veto_until,
+        proposal_min_shares,
+    })?;
+
+    Ok(Response::new()
+        .add_attribute("method", "update_meta_gov_config")
+        .add_attribute("quorum", quorum.to_string())
+        .add_attribute("threshold", threshold.to_string())
+        .add_attribute("voting_period", voting_period.to_string())
+        .add_attribute("veto_until", veto_until.to_string())
+        .add_attribute("proposal_min_shares", proposal_min_shares.to_string()))
+}
+
+// validate_proposal_action checks an action the same way the owner updates are checked.
+fn validate_proposal_action(deps: Deps, action: &ProposalAction) -> Result<(), ContractError> {
+    match action {
+        ProposalAction::UpdateFeeRate { new_feerate } => {
+            feerate_limits(*new_feerate, &fee_tiers_read(deps.storage).load()?)?;
+        }
+        ProposalAction::UpdateDev { new_dev } => {
+            deps.api.addr_validate(new_dev.as_str())?;
+        }
+        ProposalAction::SetPaused { .. } => {}
+        ProposalAction::CreateGovPoll {
+            title,
+            description,
+            link,
+            execute_msgs,
+            deposit,
+        } => {
+            if deposit.is_zero() {
+                return Err(ContractError::InsufficientFunds {});
+            }
+            let config: Config = config_read(deps.storage).load()?;
+            // the deposit leaves the pool, so it is never more than the gov asks for.
+            let proposal_deposit = config.gov_kind.adapter().query_proposal_deposit(
+                &deps.querier,
+                deps.api.addr_humanize(&config.anchor_gov)?,
+            )?;
+            if *deposit > proposal_deposit {
+                return Err(ContractError::DepositExceedsProposalDeposit {});
+            }
+            config.gov_kind.adapter().create_poll_hook(
+                title.clone(),
+                description.clone(),
+                link.clone(),
+                execute_msgs.clone(),
+            )?;
+        }
+    }
+    Ok(())
+}
+
+pub fn create_proposal(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    action: ProposalAction,
+) -> Result<Response, ContractError> {
+    let meta_gov_config = meta_gov_config_read(deps.storage)
+        .may_load()?
+        .ok_or(ContractError::MetaGovDisabled {})?;
+    let proposer = deps.api.addr_canonicalize(info.sender.as_str())?;
+    let shares = user_states_read(deps.storage)
+        .may_load(proposer.as_slice())?
+        .unwrap_or_default();
+    if shares.is_zero() {
+        return Err(ContractError::NothingStaked {});
+    }
+    let total_shares = total_shares_read(deps.storage).load()?;
+    if shares < total_shares * meta_gov_config.proposal_min_shares {
+        return Err(ContractError::ProposerSharesTooLow {});
+    }
+    validate_proposal_action(deps.as_ref(), &action)?;
+
+    let id = proposal_count_read(deps.storage).load()? + 1;
+    proposal_count_store(deps.storage).save(&id)?;
+    let end_time = env.block.time.seconds() + meta_gov_config.voting_period;
+    proposals_store(deps.storage).save(
+        &id.to_be_bytes(),
+        &Proposal {
+            id,
+            proposer,
+            action,
+            yes_votes: Uint128::zero(),
+            no_votes: Uint128::zero(),
+            end_time,
+            status: ProposalStatus::InProgress,
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("method", "create_proposal")
+        .add_attribute("proposal_id", id.to_string())
+        .add_attribute("end_time", end_time.to_string()))
+}
+
+pub fn cast_vote(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    proposal_id: u64,
+    vote: ProposalVote,
+) -> Result<Response, ContractError> {
+    let key = proposal_id.to_be_bytes();
+    let mut proposal = proposals_read(deps.storage).load(&key)?;
+    if proposal.status != ProposalStatus::InProgress
+        || env.block.time.seconds() >= proposal.end_time
+    {
+        return Err(ContractError::VotingClosed {});
+    }
+    let voter = deps.api.addr_canonicalize(info.sender.as_str())?;
+    if votes_read(deps.storage, proposal_id)
+        .may_load(voter.as_slice())?
+        .is_some()
+    {
+        return Err(ContractError::AlreadyVoted {});
+    }
+    let shares = user_states_read(deps.storage)
+        .may_load(voter.as_slice())?
+        .unwrap_or_default();
+    if shares.is_zero() {
+        return Err(ContractError::NothingStaked {});
+    }
+    let weight = vote_weight(deps.storage, &voter, shares, env.block.time.seconds())?;
+
+    match vote {
+        ProposalVote::Yes => proposal.yes_votes += weight,
+        ProposalVote::No => proposal.no_votes += weight,
+    }
+    proposals_store(deps.storage).save(&key, &proposal)?;
+    votes_store(deps.storage, proposal_id).save(
+        voter.as_slice(),
+        &Vote {
+            vote: vote.clone(),
+            shares,
+            weight,
+        },
+    )?;
+    // voted shares can not leave the pool while the proposal is in progress.
+    vote_locks_store(deps.storage, &voter).save(
+        &key,
+        &VoteLock {
+            shares,
+            end_time: proposal.end_time,
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("method", "cast_vote")
+        .add_attribute("proposal_id", proposal_id.to_string())
+        .add_attribute("vote", format!("{:?}", vote))
+        .add_attribute("shares", shares.to_string())
+        .add_attribute("weight", weight.to_string()))
+}
+
+// vote_weight adds the boost of the positions still locked to shares.
+fn vote_weight(
+    storage: &dyn Storage,
+    user: &CanonicalAddr,
+    shares: Uint128,
+    now: u64,
+) -> StdResult<Uint128> {
+    let mut weight = shares;
+    for item in positions_read(storage, user).range(None, None, Order::Ascending) {
+        let (_, position) = item?;
+        if position.unlock_time > now {
+            weight += position.weight - position.shares;
+        }
+    }
+    Ok(weight)
+}
+
+/// end_proposal tallies a proposal after its voting period, anyone can call it.
+/// quorum is measured against the total shares at the time of the tally,
+/// the boost of locked positions counts towards it as well.
+pub fn end_proposal(
+    mut deps: DepsMut,
+    env: Env,
+    proposal_id: u64,
+) -> Result<Response, ContractError> {
+    let key = proposal_id.to_be_bytes();
+    let mut proposal = proposals_read(deps.storage).load(&key)?;
+    if proposal.status != ProposalStatus::InProgress {
+        return Err(ContractError::VotingClosed {});
+    }
+    if env.block.time.seconds() < proposal.end_time {
+        return Err(ContractError::VotingNotEnded {});
+    }
+    let meta_gov_config = meta_gov_config_read(deps.storage)
+        .may_load()?
+        .ok_or(ContractError::MetaGovDisabled {})?;
+
+    let total_shares = total_shares_read(deps.storage).load()?;
+    let total_votes = proposal.yes_votes + proposal.no_votes;
+    let passed = !total_votes.is_zero()
+        && total_votes >= total_shares * meta_gov_config.quorum
+        && proposal.yes_votes > total_votes * meta_gov_config.threshold;
+
+    // the vault may have changed since the proposal was created, an action that is no
+    // longer valid fails the proposal instead of keeping it in progress forever.
+    let failure = if passed {
+        validate_proposal_action(deps.as_ref(), &proposal.action).err()
+    } else {
+        None
+    };
+    let mut messages: Vec<SubMsg> = vec![];
+    if failure.is_some() {
+        proposal.status = ProposalStatus::Failed;
+    } else if passed {
+        match proposal.action.clone() {
+            ProposalAction::UpdateFeeRate { new_feerate } => {
+                feerate_store(deps.storage).save(&new_feerate)?;
+            }
+            ProposalAction::UpdateDev { new_dev } => {
+                let mut config: Config = config_read(deps.storage).load()?;
+                config.dev = deps.api.addr_canonicalize(new_dev.as_str())?;
+                config_store(deps.storage).save(&config)?;
+            }
+            ProposalAction::SetPaused { paused } => {
+                paused_store(deps.storage).save(&paused)?;
+            }
+            ProposalAction::CreateGovPoll {
+                title,
+                description,
+                link,
+                execute_msgs,
+                deposit,
+            } => {
+                messages.push(gov_poll_withdraw_msg(
+                    deps.branch(),
+                    env.clone(),
+                    TempPollData {
+                        title,
+                        description,
+                        link,
+                        execute_msgs,
+                        deposit,
+                        funder: None,
+                    },
+                )?);
+            }
+        }
+        proposal.status = ProposalStatus::Passed;
+    } else {
+        proposal.status = ProposalStatus::Rejected;
+    }
+    proposals_store(deps.storage).save(&key, &proposal)?;
+
+    let mut res = Response::new()
+        .add_submessages(messages)
+        .add_attribute("method", "end_proposal")
+        .add_attribute("proposal_id", proposal_id.to_string())
+        .add_attribute("status", format!("{:?}", proposal.status));
+    if let Some(err) = failure {
+        res = res.add_attribute("error", err.to_string());
+    }
+    Ok(res)
+}
+
+/// create_gov_poll creates a gov poll with the ANC sent by owner as deposit.
+pub fn create_gov_poll(
+    deps: DepsMut,
+    sender: Addr,
+    poll: TempPollData,
+) -> Result<Response, ContractError> {
+    let config: Config = config_read(deps.storage).load()?;
+    assert_owner(deps.api, &config, &sender)?;
+    if poll.deposit.is_zero() {
+        return Err(ContractError::InsufficientFunds {});
+    }
+    let deposit = poll.deposit;
+    temp_poll_store(deps.storage).save(&poll)?;
+
+    Ok(Response::new()
+        .add_submessage(create_poll_msg(&config, deps.api, poll)?)
+        .add_attribute("method", "create_gov_poll")
+        .add_attribute("deposit", deposit.to_string()))
+}
+
+// create_poll_msg sends the deposit to gov with the poll, the poll id is parsed in reply.
+fn create_poll_msg(config: &Config, api: &dyn Api, poll: TempPollData) -> StdResult<SubMsg> {
+    Ok(SubMsg::reply_on_success(
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: api.addr_humanize(&config.anchor_token)?.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Send {
+                contract: api.addr_humanize(&config.anchor_gov)?.to_string(),
+                amount: poll.deposit,
+                msg: config.gov_kind.adapter().create_poll_hook(
+                    poll.title,
+                    poll.description,
+                    poll.link,
+                    poll.execute_msgs,
+                )?,
+            })?,
+            funds: vec![],
+        }),
+        CREATE_POLL_REPLY_ID,
+    ))
+}
+
+// gov_poll_withdraw_msg withdraws the poll deposit from gov, the poll is created in reply.
+fn gov_poll_withdraw_msg(
+    deps: DepsMut,
+    env: Env,
+    poll: TempPollData,
+) -> Result<SubMsg, ContractError> {
+    let config: Config = config_read(deps.storage).load()?;
+    let deposit = poll.deposit;
+    temp_poll_store(deps.storage).save(&poll)?;
+    let (available_balance, locked_balance, gov_share) = query_balance_from_gov(
+        &deps.querier,
+        &config.gov_kind,
+        deps.api.addr_humanize(&config.anchor_gov)?,
+        env.contract.address,
+    )?;
+    let total_shares = total_shares_read(deps.storage).load()?;
+    gov_checkpoint_store(deps.storage).save(&gov_checkpoint(
+        available_balance + locked_balance,
+        gov_share,
+        Uint128::zero(),
+        deposit,
+        total_shares,
+    ))?;
+
+    Ok(SubMsg::reply_on_success(
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: deps.api.addr_humanize(&config.anchor_gov)?.to_string(),
+            msg: config.gov_kind.adapter().withdraw_msg(Some(deposit))?,
+            funds: vec![],
+        }),
+        CREATE_POLL_WITHDRAW_REPLY_ID,
+    ))
+}
+
+/// veto_proposal lets the owner cancel a proposal during the transition period.
+pub fn veto_proposal(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    proposal_id: u64,
+) -> Result<Response, ContractError> {
+    let config: Config = config_read(deps.storage).load()?;
+    assert_owner(deps.api, &config, &info.sender)?;
+    let meta_gov_config = meta_gov_config_read(deps.storage)
+        .may_load()?
+        .ok_or(ContractError::MetaGovDisabled {})?;
+    if env.block.time.seconds() >= meta_gov_config.veto_until {
+        return Err(ContractError::VetoExpired {});
+    }
+
+    let key = proposal_id.to_be_bytes();
+    let mut proposal = proposals_read(deps.storage).load(&key)?;
+    if proposal.status != ProposalStatus::InProgress {
+        return Err(ContractError::VotingClosed {});
+    }
+    proposal.status = ProposalStatus::Vetoed;
+    proposals_store(deps.storage).save(&key, &proposal)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "veto_proposal")
+        .add_attribute("proposal_id", proposal_id.to_string()))
+}
+
+// release_vote_locks drops the locks of closed proposals and returns the largest share count
+// still locked, the same shares can vote on several proposals.
+fn release_vote_locks(
+    storage: &mut dyn Storage,
+    voter: &CanonicalAddr,
+    now: u64,
+) -> StdResult<Uint128> {
+    let locks = vote_locks_read(storage, voter)
+        .range(None, None, Order::Ascending)
+        .collect::<StdResult<Vec<(Vec<u8>, VoteLock)>>>()?;
+
+    let mut locked_shares = Uint128::zero();
+    for (key, lock) in locks.iter() {
+        let status = proposals_read(storage).load(key)?.status;
+        if lock.end_time > now && status == ProposalStatus::InProgress {
+            locked_shares = std::cmp::max(locked_shares, lock.shares);
+        } else {
+            vote_locks_store(storage, voter).remove(key);
+        }
+    }
+    Ok(locked_shares)
+}
+
+pub fn update_dev_vesting(
+    deps: DepsMut,
+    info: MessageInfo,
+    duration: u64,
+) -> Result<Response, ContractError> {
+    let config: Config = config_read(deps.storage).load()?;
+    assert_owner(deps.api, &config, &info.sender)?;
+    if duration > MAX_VESTING_DURATION {
+        return Err(ContractError::DurationTooLong {});
+    }
+
+    dev_vesting_store(deps.storage).save(&duration)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "update_dev_vesting")
+        .add_attribute("duration", duration.to_string()))
+}
+
+pub fn update_lock_config(
+    deps: DepsMut,
+    info: MessageInfo,
+    max_lock_duration: u64,
+    max_boost: Decimal,
+) -> Result<Response, ContractError> {
+    let config: Config = config_read(deps.storage).load()?;
+    assert_owner(deps.api, &config, &info.sender)?;
+    if max_lock_duration > MAX_LOCK_DURATION {
+        return Err(ContractError::DurationTooLong {});
+    }
+    if max_boost > Decimal::percent(MAX_BOOST) {
+        return Err(ContractError::BoostOutOfLimits {});
+    }
+
+    lock_config_store(deps.storage).save(&LockConfig {
+        max_lock_duration,
+        max_boost,
+    })?;
+
+    Ok(Response::new()
+        .add_attribute("method", "update_lock_config")
+        .add_attribute("max_lock_duration", max_lock_duration.to_string())
+        .add_attribute("max_boost", max_boost.to_string()))
+}
+
+// release_positions drops the expired positions of user and returns the shares still locked.
+fn release_positions(
+    storage: &mut dyn Storage,
+    user: &CanonicalAddr,
+    now: u64,
+) -> StdResult<Uint128> {
+    let positions = positions_read(storage, user)
+        .range(None, None, Order::Ascending)
+        .map(|item| Ok(item?.1))
+        .collect::<StdResult<Vec<Position>>>()?;
+
+    let mut locked_shares = Uint128::zero();
+    for position in positions.iter() {
+        if position.unlock_time > now {
+            locked_shares += position.shares;
+        } else {
+            positions_store(storage, user).remove(&position.id.to_be_bytes());
+        }
+    }
+    Ok(locked_shares)
+}
+
+fn unvested_shares(schedule: &VestingSchedule, now: u64) -> Uint128 {
+    if now >= schedule.end {
+        return Uint128::zero();
+    }
+    if now <= schedule.start {
+        return schedule.shares;
+    }
+    schedule
+        .shares
+        .multiply_ratio(schedule.end - now, schedule.end - schedule.start)
+}
+
+// release_vesting drops the finished vesting schedules of address and returns the shares still unvested.
+fn release_vesting(
+    storage: &mut dyn Storage,
+    address: &CanonicalAddr,
+    now: u64,
+) -> StdResult<Uint128> {
+    let schedules = vesting_read(storage, address)
+        .range(None, None, Order::Ascending)
+        .map(|item| Ok(item?.1))
+        .collect::<StdResult<Vec<VestingSchedule>>>()?;
+
+    let mut unvested = Uint128::zero();
+    for schedule in schedules.iter() {
+        if schedule.end > now {
+            unvested += unvested_shares(schedule, now);
+        } else {
+            vesting_store(storage, address).remove(&schedule.start.to_be_bytes());
+        }
+    }
+    Ok(unvested)
+}
+
+// gov_checkpoint returns the gov stake expected once `staked` and `withdrawn` are settled,
+// gov shares move at the gov exchange rate before the operation.
+fn gov_checkpoint(
+    gov_balance: Uint128,
+    gov_share: Uint128,
+    staked: Uint128,
+    withdrawn: Uint128,
+    total_shares: Uint128,
+) -> GovCheckpoint {
+    let to_share = |amount: Uint128| {
+        if gov_balance.is_zero() {
+            amount
+        } else {
+            amount.multiply_ratio(gov_share, gov_balance)
+        }
+    };
+    // gov converts at its own totals and the vault ratio is floored from them, so a conversion
+    // can be off by one share per gov_balance converted, plus one. with nothing staked yet the
+    // vault has no ratio to predict from.
+    let tolerance = |amount: Uint128| {
+        if amount.is_zero() {
+            Uint128::zero()
+        } else if gov_balance.is_zero() {
+            amount
+        } else {
+            to_share(amount).multiply_ratio(1u128, gov_balance) + Uint128::from(1u128)
+        }
+    };
+    GovCheckpoint {
+        gov_balance: gov_balance + staked - withdrawn,
+        gov_share: gov_share + to_share(staked) - to_share(withdrawn),
+        total_shares,
+        share_tolerance: tolerance(staked) + tolerance(withdrawn),
+    }
+}
+
+pub fn update_deposit_limits(
+    deps: DepsMut,
+    info: MessageInfo,
+    max_total_deposit: Option<Uint128>,
+    max_per_user: Option<Uint128>,
+) -> Result<Response, ContractError> {
+    let config: Config = config_read(deps.storage).load()?;
+    assert_owner(deps.api, &config, &info.sender)?;
+
+    deposit_limits_store(deps.storage).save(&DepositLimits {
+        max_total_deposit,
+        max_per_user,
+    })?;
+
+    let to_attr = |x: Option<Uint128>| x.map(|v| v.to_string()).unwrap_or_default();
+    Ok(Response::new()
+        .add_attribute("method", "update_deposit_limits")
+        .add_attribute("max_total_deposit", to_attr(max_total_deposit))
+        .add_attribute("max_per_user", to_attr(max_per_user)))
+}
+
+/// set_allowlist_enabled turns the private vault mode on or off.
+pub fn set_allowlist_enabled(
+    deps: DepsMut,
+    info: MessageInfo,
+    enabled: bool,
+) -> Result<Response, ContractError> {
+    let config: Config = config_read(deps.storage).load()?;
+    assert_owner(deps.api, &config, &info.sender)?;
+
+    allowlist_enabled_store(deps.storage).save(&enabled)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "set_allowlist_enabled")
+        .add_attribute("enabled", enabled.to_string()))
+}
+
+/// update_allowlist adds and removes depositors in bulk, removals are applied last.
+pub fn update_allowlist(
+    deps: DepsMut,
+    info: MessageInfo,
+    add: Vec<String>,
+    remove: Vec<String>,
+) -> Result<Response, ContractError> {
+    let config: Config = config_read(deps.storage).load()?;
+    assert_owner(deps.api, &config, &info.sender)?;
+
+    for addr in add.iter() {
+        let key = deps.api.addr_canonicalize(addr.as_str())?;
+        allowlist_store(deps.storage).save(key.as_slice(), &true)?;
+    }
+    for addr in remove.iter() {
+        let key = deps.api.addr_canonicalize(addr.as_str())?;
+        allowlist_store(deps.storage).remove(key.as_slice());
+    }
+
+    Ok(Response::new()
+        .add_attribute("method", "update_allowlist")
+        .add_attribute("added", add.len().to_string())
+        .add_attribute("removed", remove.len().to_string()))
+}
+
+/// update_fee_exemptions manages the depositors that do not pay `feerate`.
+pub fn update_fee_exemptions(
+    deps: DepsMut,
+    info: MessageInfo,
+    add: Vec<String>,
+    remove: Vec<String>,
+) -> Result<Response, ContractError> {
+    let config: Config = config_read(deps.storage).load()?;
+    assert_owner(deps.api, &config, &info.sender)?;
+
+    for addr in add.iter() {
+        let key = deps.api.addr_canonicalize(addr.as_str())?;
+        fee_exemptions_store(deps.storage).save(key.as_slice(), &true)?;
+    }
+    for addr in remove.iter() {
+        let key = deps.api.addr_canonicalize(addr.as_str())?;
+        fee_exemptions_store(deps.storage).remove(key.as_slice());
+    }
+
+    Ok(Response::new()
+        .add_attribute("method", "update_fee_exemptions")
+        .add_attribute("added", add.len().to_string())
+        .add_attribute("removed", remove.len().to_string()))
+}
+
+pub fn update_hooks(
+    deps: DepsMut,
+    info: MessageInfo,
+    add: Vec<HookInfo>,
+    remove: Vec<String>,
+) -> Result<Response, ContractError> {
+    let config: Config = config_read(deps.storage).load()?;
+    assert_owner(deps.api, &config, &info.sender)?;
+
+    let mut hooks = hooks_read(deps.storage).may_load()?.unwrap_or_default();
+    for hook in add.iter() {
+        let contract = deps.api.addr_canonicalize(hook.contract.as_str())?;
+        hooks.retain(|x| x.contract != contract);
+        hooks.push(Hook {
+            contract,
+            fatal: hook.fatal,
+        });
+    }
+    for addr in remove.iter() {
+        let contract = deps.api.addr_canonicalize(addr.as_str())?;
+        hooks.retain(|x| x.contract != contract);
+    }
+    if hooks.len() > MAX_HOOKS {
+        return Err(ContractError::TooManyHooks {});
+    }
+    hooks_store(deps.storage).save(&hooks)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "update_hooks")
+        .add_attribute("added", add.len().to_string())
+        .add_attribute("removed", remove.len().to_string()))
+}
+
+pub fn register_airdrop(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    token: String,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    let config: Config = config_read(deps.storage).load()?;
+    assert_owner(deps.api, &config, &info.sender)?;
+    let token_raw = deps.api.addr_canonicalize(token.as_str())?;
+    if token_raw == config.anchor_token {
+        return Err(ContractError::InvalidAirdropToken {});
+    }
+    // shares changed in this block after the airdrop landed are not counted.
+    let height = env.block.height;
+    if airdrops_read(deps.storage, &token_raw)
+        .may_load(&height.to_be_bytes())?
+        .is_some()
+    {
+        return Err(ContractError::AirdropExists {});
+    }
+    let balance = query_token_balance(
+        &deps.querier,
+        deps.api.addr_validate(token.as_str())?,
+        env.contract.address,
+    )?;
+    // earlier airdrops of the token keep what their holders have not claimed yet.
+    let unclaimed = unclaimed_airdrops(deps.storage, &token_raw)?;
+    if amount.is_zero() || amount > balance.checked_sub(unclaimed)? {
+        return Err(ContractError::InsufficientFunds {});
+    }
+    let total_shares = total_shares_at_height(deps.storage, height)?;
+    if total_shares.is_zero() {
+        return Err(ContractError::NothingStaked {});
+    }
+    airdrops_store(deps.storage, &token_raw).save(
+        &height.to_be_bytes(),
+        &Airdrop {
+            height,
+            amount,
+            total_shares,
+            claimed: Uint128::zero(),
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("method", "register_airdrop")
+        .add_attribute("token", token)
+        .add_attribute("amount", amount.to_string())
+        .add_attribute("height", height.to_string()))
+}
+
+fn airdrop_share(
+    storage: &dyn Storage,
+    airdrop: &Airdrop,
+    user: &CanonicalAddr,
+) -> StdResult<Uint128> {
+    let shares = user_shares_at_height(storage, user, airdrop.height)?;
+    Ok(airdrop.amount.multiply_ratio(shares, airdrop.total_shares))
+}
+
+pub fn claim_airdrop(
+    deps: DepsMut,
+    info: MessageInfo,
+    token: String,
+    height: u64,
+) -> Result<Response, ContractError> {
+    let token_raw = deps.api.addr_canonicalize(token.as_str())?;
+    let user_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
+    let mut airdrop = airdrops_read(deps.storage, &token_raw).load(&height.to_be_bytes())?;
+    if airdrop_claims_read(deps.storage, &token_raw, height)
+        .may_load(user_raw.as_slice())?
+        .is_some()
+    {
+        return Err(ContractError::AlreadyClaimed {});
+    }
+    let amount = airdrop_share(deps.storage, &airdrop, &user_raw)?;
+    if amount.is_zero() {
+        return Err(ContractError::NothingToClaim {});
+    }
+    airdrop_claims_store(deps.storage, &token_raw, height).save(user_raw.as_slice(), &amount)?;
+    airdrop.claimed += amount;
+    airdrops_store(deps.storage, &token_raw).save(&height.to_be_bytes(), &airdrop)?;
+
+    transfer_tokens(deps, &token_raw, &user_raw, amount, "claim_airdrop")
+}
+
+pub fn update_compound_incentive(
+    deps: DepsMut,
+    info: MessageInfo,
+    rate: Decimal,
+) -> Result<Response, ContractError> {
+    let config: Config = config_read(deps.storage).load()?;
+    assert_owner(deps.api, &config, &info.sender)?;
+    if rate > Decimal::percent(MAX_COMPOUND_INCENTIVE) {
+        return Err(ContractError::CompoundIncentiveOutOfLimits {});
+    }
+
+    compound_incentive_store(deps.storage).save(&rate)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "update_compound_incentive")
+        .add_attribute("rate", rate.to_string()))
+}
+
+/// compound stakes the ANC left in the vault, e.g. gov rewards, without minting shares,
+/// so the balance per share goes up for every holder.
+/// unsolicited ANC under `Refund` is kept for the owner, so there is nothing to compound.
+/// a poll deposit refunded by gov waits for its settlement and is not compounded either.
+pub fn compound(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+    if paused_read(deps.storage).load()? {
+        return Err(ContractError::Paused {});
+    }
+    if stray_anc_policy_read(deps.storage).load()? != StrayAncPolicy::CreditPool {
+        return Err(ContractError::NothingToCompound {});
+    }
+    let total_shares = total_shares_read(deps.storage).load()?;
+    if total_shares.is_zero() {
+        return Err(ContractError::NothingStaked {});
+    }
+    let config: Config = config_read(deps.storage).load()?;
+    let balance = query_token_balance(
+        &deps.querier,
+        deps.api.addr_humanize(&config.anchor_token)?,
+        env.contract.address.clone(),
+    )?;
+    let outstanding = outstanding_poll_deposits_read(deps.storage)
+        .may_load()?
+        .unwrap_or_default();
+    let idle = balance.saturating_sub(claimable_fees_read(deps.storage).load()? + outstanding);
+    let incentive = idle
+        * compound_incentive_read(deps.storage)
+            .may_load()?
+            .unwrap_or_default();
+    let stake_amount = idle - incentive;
+    if stake_amount.is_zero() {
+        return Err(ContractError::NothingToCompound {});
+    }
+
+    let (available_balance, locked_balance, gov_share) = query_balance_from_gov(
+        &deps.querier,
+        &config.gov_kind,
+        deps.api.addr_humanize(&config.anchor_gov)?,
+        env.contract.address,
+    )?;
+    let total_balance = available_balance + locked_balance;
+    let compound = Compound {
+        height: env.block.height,
+        time: env.block.time.seconds(),
+        staked: stake_amount,
+        incentive,
+        exchange_rate_before: Decimal::from_ratio(total_balance, total_shares),
+        exchange_rate_after: Decimal::from_ratio(total_balance + stake_amount, total_shares),
+    };
+    last_compound_store(deps.storage).save(&compound)?;
+    gov_checkpoint_store(deps.storage).save(&gov_checkpoint(
+        total_balance,
+        gov_share,
+        stake_amount,
+        Uint128::zero(),
+        total_shares,
+    ))?;
+
+    let anchor_token = deps.api.addr_humanize(&config.anchor_token)?.to_string();
+    let mut res = send_tokens(
+        deps,
+        &config.anchor_token,
+        &config.anchor_gov,
+        stake_amount,
+        config.gov_kind.adapter().stake_hook()?,
+        vec![
+            attr("method", "compound"),
+            attr("staked", stake_amount.to_string()),
+            attr("incentive", incentive.to_string()),
+            attr(
+                "exchange_rate_before",
+                compound.exchange_rate_before.to_string(),
+            ),
+            attr(
+                "exchange_rate_after",
+                compound.exchange_rate_after.to_string(),
+            ),
+        ],
+    )?;
+    if !incentive.is_zero() {
+        res = res.add_message(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: anchor_token,
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: info.sender.to_string(),
+                amount: incentive,
+            })?,
+            funds: vec![],
+        }));
+    }
+    Ok(res)
+}
+
+/// settle_gov_poll hands the deposit gov refunded for an ended poll back to whoever paid it:
+/// the owner gets it transferred, a pool-funded deposit is staked back into the pool whatever
+/// the stray ANC policy. a deposit kept by gov, the poll missed quorum, settles with nothing sent.
+pub fn settle_gov_poll(deps: DepsMut, env: Env, poll_id: u64) -> Result<Response, ContractError> {
+    let mut poll = gov_polls_read(deps.storage).load(&poll_id.to_be_bytes())?;
+    if poll.settled {
+        return Err(ContractError::PollSettled {});
+    }
+    let config: Config = config_read(deps.storage).load()?;
+    let gov_addr = deps.api.addr_humanize(&config.anchor_gov)?;
+    let returned = match config.gov_kind.adapter().query_poll_outcome(
+        &deps.querier,
+        gov_addr.clone(),
+        poll_id,
+    )? {
+        PollOutcome::InProgress => return Err(ContractError::PollInProgress {}),
+        PollOutcome::DepositKept => Uint128::zero(),
+        PollOutcome::DepositReturned => poll.deposit,
+    };
+    if !returned.is_zero() {
+        let balance = query_token_balance(
+            &deps.querier,
+            deps.api.addr_humanize(&config.anchor_token)?,
+            env.contract.address.clone(),
+        )?;
+        if balance.checked_sub(claimable_fees_read(deps.storage).load()?)? < returned {
+            return Err(ContractError::InsufficientFunds {});
+        }
+    }
+    let outstanding = outstanding_poll_deposits_read(deps.storage)
+        .may_load()?
+        .unwrap_or_default();
+    outstanding_poll_deposits_store(deps.storage).save(&outstanding.checked_sub(poll.deposit)?)?;
+    if poll.funder.is_none() {
+        let pool_deposits = pool_poll_deposits_read(deps.storage)
+            .may_load()?
+            .unwrap_or_default();
+        pool_poll_deposits_store(deps.storage).save(&pool_deposits.checked_sub(poll.deposit)?)?;
+    }
+    poll.settled = true;
+    gov_polls_store(deps.storage).save(&poll_id.to_be_bytes(), &poll)?;
+
+    let attrs = vec![
+        attr("method", "settle_gov_poll"),
+        attr("poll_id", poll_id.to_string()),
+        attr("returned", returned.to_string()),
+    ];
+    if returned.is_zero() {
+        return Ok(Response::new().add_attributes(attrs));
+    }
+    if let Some(funder) = poll.funder {
+        return Ok(Response::new()
+            .add_message(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: deps.api.addr_humanize(&config.anchor_token)?.to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: deps.api.addr_humanize(&funder)?.to_string(),
+                    amount: returned,
+                })?,
+                funds: vec![],
+            }))
+            .add_attributes(attrs));
+    }
+
+    let (available_balance, locked_balance, gov_share) = query_balance_from_gov(
+        &deps.querier,
+        &config.gov_kind,
+        gov_addr,
+        env.contract.address,
+    )?;
+    let total_shares = total_shares_read(deps.storage).load()?;
+    gov_checkpoint_store(deps.storage).save(&gov_checkpoint(
+        available_balance + locked_balance,
+        gov_share,
+        returned,
+        Uint128::zero(),
+        total_shares,
+    ))?;
+    send_tokens(
+        deps,
+        &config.anchor_token,
+        &config.anchor_gov,
+        returned,
+        config.gov_kind.adapter().stake_hook()?,
+        attrs,
+    )
+}
+
+pub fn commit_merkle_root(
     deps: DepsMut,
+    env: Env,
     info: MessageInfo,
-    new_feerate: Decimal,
+    height: u64,
+    merkle_root: String,
+    replace: bool,
 ) -> Result<Response, ContractError> {
     let config: Config = config_read(deps.storage).load()?;
-    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
-        return Err(ContractError::Unauthorized {});
+    assert_owner(deps.api, &config, &info.sender)?;
+    // shares at the beginning of a height are final once the height is reached.
+    if height > env.block.height {
+        return Err(ContractError::SnapshotNotFinal {});
     }
-    let old_feerate = feerate_read(deps.storage).load()?;
-    // feerate range check.
-    let new_feerate = feerate_limits(new_feerate)?;
+    merkle::decode_hash(&merkle_root).map_err(|_| ContractError::InvalidMerkleRoot {})?;
+    // airdrops may already verify against the committed root.
+    let replaced = merkle_roots_read(deps.storage).may_load(&height.to_be_bytes())?;
+    if replaced.is_some() && !replace {
+        return Err(ContractError::MerkleRootExists {});
+    }
+    let total_shares = total_shares_at_height(deps.storage, height)?;
+    merkle_roots_store(deps.storage).save(
+        &height.to_be_bytes(),
+        &MerkleRoot {
+            merkle_root: merkle_root.clone(),
+            total_shares,
+        },
+    )?;
 
-    // store feerate to state value.
-    feerate_store(deps.storage).save(&new_feerate)?;
+    let mut res = Response::new()
+        .add_attribute("method", "commit_merkle_root")
+        .add_attribute("height", height.to_string())
+        .add_attribute("merkle_root", merkle_root.clone());
+    if let Some(old) = replaced {
+        res = res.add_event(
+            Event::new("merkle_root_replaced")
+                .add_attribute("height", height.to_string())
+                .add_attribute("old_merkle_root", old.merkle_root)
+                .add_attribute("merkle_root", merkle_root),
+        );
+    }
+    Ok(res)
+}
 
-    Ok(Response::new()
-        .add_attribute("method", "update_feerate")
-        .add_attribute("old_feerate", old_feerate.to_string())
-        .add_attribute("new_feerate", new_feerate.to_string()))
+// hook_msgs notifies every hook of the share change of user,
+// failures of best-effort hooks are caught in reply.
+fn hook_msgs(
+    deps: Deps,
+    user: &CanonicalAddr,
+    delta: ShareDelta,
+    shares: Uint128,
+) -> StdResult<Vec<SubMsg>> {
+    let hooks = hooks_read(deps.storage).may_load()?.unwrap_or_default();
+    if hooks.is_empty() {
+        return Ok(vec![]);
+    }
+    let msg = to_binary(&HookMsg::SharesChanged {
+        user: deps.api.addr_humanize(user)?.to_string(),
+        delta,
+        shares,
+    })?;
+    hooks
+        .iter()
+        .map(|hook| {
+            let execute = CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: deps.api.addr_humanize(&hook.contract)?.to_string(),
+                msg: msg.clone(),
+                funds: vec![],
+            });
+            Ok(if hook.fatal {
+                SubMsg::new(execute)
+            } else {
+                SubMsg::reply_on_error(execute, HOOK_REPLY_ID)
+            })
+        })
+        .collect()
 }
-// feerate_limits to check new fee rate in range
-fn feerate_limits(feerate: Decimal) -> Result<Decimal, ContractError> {
+
+// feerate_limits to check new fee rate in range, tiers must have strictly increasing
+// amounts and non-increasing rates starting from the base fee rate.
+fn feerate_limits(feerate: Decimal, tiers: &[FeeTier]) -> Result<Decimal, ContractError> {
     if feerate > Decimal::one() || feerate < Decimal::zero() {
         return Err(ContractError::FeeRateOutOfLimits {});
     }
+    let mut last_feerate = feerate;
+    let mut last_amount: Option<Uint128> = None;
+    for tier in tiers.iter() {
+        if tier.feerate > Decimal::one() || tier.feerate < Decimal::zero() {
+            return Err(ContractError::FeeRateOutOfLimits {});
+        }
+        if tier.feerate > last_feerate || matches!(last_amount, Some(x) if tier.min_amount <= x) {
+            return Err(ContractError::InvalidFeeTiers {});
+        }
+        last_feerate = tier.feerate;
+        last_amount = Some(tier.min_amount);
+    }
     Ok(feerate)
 }
 
+// tiered_feerate returns the rate of the highest tier reached by amount.
+fn tiered_feerate(feerate: Decimal, tiers: &[FeeTier], amount: Uint128) -> Decimal {
+    tiers
+        .iter()
+        .rev()
+        .find(|x| amount >= x.min_amount)
+        .map_or(feerate, |x| x.feerate)
+}
+
 /// withdraw token from gov to user.
 pub fn withdraw_token(
     deps: DepsMut,
@@ -186,17 +1761,36 @@ pub fn withdraw_token(
     info: MessageInfo,
     amount: Option<Uint128>,
 ) -> Result<Response, ContractError> {
+    if paused_read(deps.storage).load()? {
+        return Err(ContractError::Paused {});
+    }
     let user_raw = deps.api.addr_canonicalize(info.sender.as_str())?;
     let key = user_raw.as_slice();
-    if let Some(mut user_shares) = user_states_read(deps.storage).may_load(key)? {
+    let user_shares = user_states_read(deps.storage)
+        .may_load(key)?
+        .filter(|x| !x.is_zero());
+    if let Some(mut user_shares) = user_shares {
+        let now = env.block.time.seconds();
+        let locked_shares = std::cmp::max(
+            release_positions(deps.storage, &user_raw, now)?
+                + release_vesting(deps.storage, &user_raw, now)?,
+            release_vote_locks(deps.storage, &user_raw, now)?,
+        );
+        let unlocked_shares = user_shares.checked_sub(locked_shares)?;
         let config: Config = config_read(deps.storage).load()?;
         let mut total_shares = total_shares_read(deps.storage).load()?;
-        let (available_balance, locked_balance, _) = query_balance_from_gov(
+        let (available_balance, locked_balance, gov_share) = query_balance_from_gov(
             &deps.querier,
+            &config.gov_kind,
             deps.api.addr_humanize(&config.anchor_gov)?,
             env.contract.address,
         )?;
-        let total_balance = available_balance + locked_balance;
+        let gov_balance = available_balance + locked_balance;
+        // a deposit of a pool-funded poll is out of gov but still priced into the shares.
+        let total_balance = gov_balance
+            + pool_poll_deposits_read(deps.storage)
+                .may_load()?
+                .unwrap_or_default();
         let withdraw_shares = amount
             .map(|v| {
                 std::cmp::max(
@@ -204,7 +1798,7 @@ pub fn withdraw_token(
                     Uint128::from(1u128),
                 )
             })
-            .unwrap_or(user_shares);
+            .unwrap_or(unlocked_shares);
 
         let available_user_shares = total_shares
             .multiply_ratio(available_balance, total_balance)
@@ -212,29 +1806,86 @@ pub fn withdraw_token(
         if withdraw_shares > available_user_shares {
             return Err(ContractError::InsufficientFunds {});
         }
+        if withdraw_shares > unlocked_shares
+            || (withdraw_shares.is_zero() && !locked_shares.is_zero())
+        {
+            return Err(ContractError::SharesLocked {});
+        }
 
-        let withdraw_amount: Uint128 = withdraw_shares.multiply_ratio(total_balance, total_shares);
+        let mut withdraw_amount: Uint128 =
+            withdraw_shares.multiply_ratio(total_balance, total_shares);
         user_shares -= withdraw_shares;
         total_shares -= withdraw_shares;
 
-        user_states_store(deps.storage).save(&key, &user_shares)?;
-        total_shares_store(deps.storage).save(&total_shares)?;
+        // early exit fee stays in gov, so the remaining shares are worth more.
+        // the last holder leaving has nobody to pay it to.
+        let exit_fee = exit_fee_read(deps.storage).load()?;
+        let remaining = seconds_to_free_exit(
+            &exit_fee,
+            last_deposit_read(deps.storage).may_load(key)?,
+            env.block.time.seconds(),
+        );
+        let mut exit_fee_amount = Uint128::zero();
+        if remaining > 0 && !total_shares.is_zero() {
+            exit_fee_amount = (withdraw_amount * exit_fee.max_feerate)
+                .multiply_ratio(remaining, exit_fee.duration);
+            withdraw_amount -= exit_fee_amount;
+        }
+
+        // a full exit leaves no entry behind.
+        let full_exit = user_shares.is_zero();
+        if full_exit {
+            last_deposit_store(deps.storage).remove(key);
+        }
+        save_user_shares(
+            deps.storage,
+            env.block.height,
+            env.block.time.seconds(),
+            &user_raw,
+            user_shares,
+        )?;
+        save_total_shares(
+            deps.storage,
+            env.block.height,
+            env.block.time.seconds(),
+            total_shares,
+        )?;
+        gov_checkpoint_store(deps.storage).save(&gov_checkpoint(
+            gov_balance,
+            gov_share,
+            Uint128::zero(),
+            withdraw_amount,
+            total_shares,
+        ))?;
         // waitting for send after receiving
         temp_send_store(deps.storage).save(&TempSendData {
             recipient: info.sender.to_string(),
             amount: withdraw_amount,
         })?;
 
-        Ok(Response::new().add_submessage(SubMsg::reply_on_success(
-            CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: deps.api.addr_humanize(&config.anchor_gov)?.to_string(),
-                msg: to_binary(&GovExcuteMsg::WithdrawVotingTokens {
-                    amount: Some(withdraw_amount),
-                })?,
-                funds: vec![],
-            }),
-            WITHDRAW_REPLY_ID,
-        )))
+        Ok(Response::new()
+            .add_attribute("method", "withdraw_token")
+            .add_attribute("exit_fee", exit_fee_amount.to_string())
+            .add_attribute("full_exit", full_exit.to_string())
+            .add_submessage(SubMsg::reply_on_success(
+                CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: deps.api.addr_humanize(&config.anchor_gov)?.to_string(),
+                    msg: config
+                        .gov_kind
+                        .adapter()
+                        .withdraw_msg(Some(withdraw_amount))?,
+                    funds: vec![],
+                }),
+                WITHDRAW_REPLY_ID,
+            ))
+            .add_submessages(hook_msgs(
+                deps.as_ref(),
+                &user_raw,
+                ShareDelta::Decrease {
+                    amount: withdraw_shares,
+                },
+                user_shares,
+            )?))
     } else {
         Err(ContractError::NothingStaked {})
     }
@@ -251,13 +1902,38 @@ pub fn receive_cw20(
         return Err(ContractError::Unauthorized {});
     }
     match from_binary(&cw20_msg.msg) {
-        Ok(Cw20HookMsg::StakingTokens {}) => {
+        Ok(Cw20HookMsg::StakingTokens { lock_duration }) => {
             let api = deps.api;
-            stake_tokens(
+            let sender = api.addr_validate(&cw20_msg.sender)?;
+            // withdrawals are never restricted, only new deposits.
+            if allowlist_enabled_read(deps.storage).load()?
+                && allowlist_read(deps.storage)
+                    .may_load(api.addr_canonicalize(sender.as_str())?.as_slice())?
+                    .is_none()
+            {
+                return Err(ContractError::NotAllowlisted {});
+            }
+            stake_tokens(deps, env, sender, cw20_msg.amount, lock_duration)
+        }
+        Ok(Cw20HookMsg::CreateGovPoll {
+            title,
+            description,
+            link,
+            execute_msgs,
+        }) => {
+            let sender = deps.api.addr_validate(&cw20_msg.sender)?;
+            let funder = deps.api.addr_canonicalize(sender.as_str())?;
+            create_gov_poll(
                 deps,
-                env,
-                api.addr_validate(&cw20_msg.sender)?,
-                cw20_msg.amount,
+                sender,
+                TempPollData {
+                    title,
+                    description,
+                    link,
+                    execute_msgs,
+                    deposit: cw20_msg.amount,
+                    funder: Some(funder),
+                },
             )
         }
         _ => Err(ContractError::DataShouldBeGiven {}),
@@ -269,26 +1945,76 @@ fn stake_tokens(
     env: Env,
     sender: Addr,
     mut amount: Uint128,
+    lock_duration: Option<u64>,
 ) -> Result<Response, ContractError> {
+    if paused_read(deps.storage).load()? {
+        return Err(ContractError::Paused {});
+    }
     if amount.is_zero() {
         return Err(ContractError::InsufficientFunds {});
     }
+    let lock_config = lock_config_read(deps.storage).load()?;
+    if let Some(lock_duration) = lock_duration {
+        if lock_duration == 0 || lock_duration > lock_config.max_lock_duration {
+            return Err(ContractError::LockDurationOutOfLimits {});
+        }
+    }
     let config: Config = config_read(deps.storage).load()?;
-    let (available_balance, locked_balance, _) = query_balance_from_gov(
+    let (available_balance, locked_balance, gov_share) = query_balance_from_gov(
         &deps.querier,
+        &config.gov_kind,
         deps.api.addr_humanize(&config.anchor_gov)?,
         env.contract.address.clone(),
     )?;
-    let deposited_balance = available_balance + locked_balance;
+    let gov_balance = available_balance + locked_balance;
+    // a deposit of a pool-funded poll is out of gov but still priced into the shares.
+    let deposited_balance = gov_balance
+        + pool_poll_deposits_read(deps.storage)
+            .may_load()?
+            .unwrap_or_default();
     let sender_address_raw = deps.api.addr_canonicalize(sender.as_str())?;
     let key = &sender_address_raw.as_slice();
 
     let mut total_shares = total_shares_read(deps.storage).load()?;
 
-    let feerate = feerate_read(deps.storage).load()?;
+    // deposit caps are checked against the gross amount, before dev fee.
+    let limits = deposit_limits_read(deps.storage).load()?;
+    if let Some(max_total_deposit) = limits.max_total_deposit {
+        if deposited_balance + amount > max_total_deposit {
+            return Err(ContractError::DepositCapExceeded {});
+        }
+    }
+    if let Some(max_per_user) = limits.max_per_user {
+        let user_shares = user_states_read(deps.storage)
+            .may_load(key)?
+            .unwrap_or_default();
+        if shares_to_balance(user_shares, deposited_balance, total_shares) + amount > max_per_user {
+            return Err(ContractError::UserDepositCapExceeded {});
+        }
+    }
+
+    let feerate = tiered_feerate(
+        feerate_read(deps.storage).load()?,
+        &fee_tiers_read(deps.storage).load()?,
+        amount,
+    );
+    let fee_exempt = fee_exemptions_read(deps.storage).may_load(key)?.is_some();
     let mut dev_increase_share = Uint128::zero();
-    let dev_amount = amount * feerate;
-    if !dev_amount.is_zero() {
+    let dev_amount = if fee_exempt {
+        Uint128::zero()
+    } else {
+        amount * feerate
+    };
+    let fee_mode = fee_mode_read(deps.storage).load()?;
+    let mut claimable_fees = claimable_fees_read(deps.storage).load()?;
+    let mut stake_amount = amount;
+    let mut hooks = vec![];
+    if !dev_amount.is_zero() && fee_mode == FeeMode::Claimable {
+        amount -= dev_amount;
+        stake_amount -= dev_amount;
+        claimable_fees += dev_amount;
+        claimable_fees_store(deps.storage).save(&claimable_fees)?;
+    } else if !dev_amount.is_zero() {
         amount -= dev_amount;
         dev_increase_share = deposit(dev_amount, deposited_balance, total_shares);
         let dev_key = &config.dev.as_slice();
@@ -296,7 +2022,36 @@ fn stake_tokens(
             .may_load(dev_key)?
             .unwrap_or_default();
         dev_shares += dev_increase_share;
-        user_states_store(deps.storage).save(dev_key, &dev_shares)?;
+        save_user_shares(
+            deps.storage,
+            env.block.height,
+            env.block.time.seconds(),
+            &config.dev,
+            dev_shares,
+        )?;
+        hooks.extend(hook_msgs(
+            deps.as_ref(),
+            &config.dev,
+            ShareDelta::Increase {
+                amount: dev_increase_share,
+            },
+            dev_shares,
+        )?);
+
+        // fee accrued in the same block shares one schedule.
+        let duration = dev_vesting_read(deps.storage).load()?;
+        if duration > 0 {
+            let start = env.block.time.seconds();
+            let mut schedule = vesting_read(deps.storage, &config.dev)
+                .may_load(&start.to_be_bytes())?
+                .unwrap_or(VestingSchedule {
+                    shares: Uint128::zero(),
+                    start,
+                    end: start + duration,
+                });
+            schedule.shares += dev_increase_share;
+            vesting_store(deps.storage, &config.dev).save(&start.to_be_bytes(), &schedule)?;
+        }
     }
     let mut user_shares = user_states_read(deps.storage)
         .may_load(key)?
@@ -304,26 +2059,82 @@ fn stake_tokens(
     let user_increase_share = deposit(amount, deposited_balance, total_shares);
     user_shares += user_increase_share;
     total_shares += dev_increase_share + user_increase_share;
-    total_shares_store(deps.storage).save(&total_shares)?;
-    user_states_store(deps.storage).save(key, &user_shares)?;
+    save_total_shares(
+        deps.storage,
+        env.block.height,
+        env.block.time.seconds(),
+        total_shares,
+    )?;
+    save_user_shares(
+        deps.storage,
+        env.block.height,
+        env.block.time.seconds(),
+        &sender_address_raw,
+        user_shares,
+    )?;
+    last_deposit_store(deps.storage).save(key, &env.block.time.seconds())?;
+    hooks.extend(hook_msgs(
+        deps.as_ref(),
+        &sender_address_raw,
+        ShareDelta::Increase {
+            amount: user_increase_share,
+        },
+        user_shares,
+    )?);
+    if let Some(lock_duration) = lock_duration {
+        // lock weight grows linearly with the lock duration.
+        let boost = (user_increase_share * lock_config.max_boost)
+            .multiply_ratio(lock_duration, lock_config.max_lock_duration);
+        let id = position_count_read(deps.storage).load()? + 1;
+        position_count_store(deps.storage).save(&id)?;
+        positions_store(deps.storage, &sender_address_raw).save(
+            &id.to_be_bytes(),
+            &Position {
+                id,
+                shares: user_increase_share,
+                weight: user_increase_share + boost,
+                unlock_time: env.block.time.seconds() + lock_duration,
+            },
+        )?;
+    }
     let balance = query_token_balance(
         &deps.querier,
         deps.api.addr_humanize(&config.anchor_token)?,
         env.contract.address,
     )?;
+    // stray ANC is only swept into gov when it is credited to the pool,
+    // a returned poll deposit waits for its settlement.
+    if stray_anc_policy_read(deps.storage).load()? == StrayAncPolicy::CreditPool {
+        let outstanding = outstanding_poll_deposits_read(deps.storage)
+            .may_load()?
+            .unwrap_or_default();
+        stake_amount = std::cmp::max(
+            balance.saturating_sub(claimable_fees + outstanding),
+            stake_amount,
+        );
+    }
+    gov_checkpoint_store(deps.storage).save(&gov_checkpoint(
+        gov_balance,
+        gov_share,
+        stake_amount,
+        Uint128::zero(),
+        total_shares,
+    ))?;
     let dev = deps.api.addr_humanize(&config.dev)?;
-    send_tokens(
+    Ok(send_tokens(
         deps,
         &config.anchor_token,
         &config.anchor_gov,
-        balance,
-        to_binary(&GovCw20HookMsg::StakeVotingTokens {}).unwrap(),
+        stake_amount,
+        config.gov_kind.adapter().stake_hook()?,
         vec![
             attr("method", "StakingTokens"),
             attr(sender.to_string(), amount.to_string()),
             attr(dev.to_string(), dev_amount.to_string()),
+            attr("fee_exempt", fee_exempt.to_string()),
         ],
-    )
+    )?
+    .add_submessages(hooks))
 }
 
 fn deposit(amount: Uint128, total_balance: Uint128, total_shares: Uint128) -> Uint128 {
@@ -332,6 +2143,12 @@ fn deposit(amount: Uint128, total_balance: Uint128, total_shares: Uint128) -> Ui
     }
     amount.multiply_ratio(total_shares, total_balance)
 }
+fn shares_to_balance(shares: Uint128, total_balance: Uint128, total_shares: Uint128) -> Uint128 {
+    if total_shares.is_zero() {
+        return Uint128::zero();
+    }
+    shares.multiply_ratio(total_balance, total_shares)
+}
 fn send_tokens(
     deps: DepsMut,
     asset_token: &CanonicalAddr,
@@ -397,22 +2214,13 @@ pub fn query_token_balance(
 
 pub fn query_balance_from_gov(
     querier: &QuerierWrapper,
+    gov_kind: &GovKind,
     gov_addr: Addr,
     contract_addr: Addr,
 ) -> StdResult<(Uint128, Uint128, Uint128)> {
-    let response: StakerResponse = querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
-        contract_addr: gov_addr.to_string(),
-        msg: to_binary(&GovQueryMsg::Staker {
-            address: contract_addr.to_string(),
-        })?,
-    }))?;
-    let mut available_balance = response.balance;
-    let mut locked_balance = Uint128::zero();
-    for (_, x) in response.locked_balance.iter() {
-        available_balance -= x.balance;
-        locked_balance += x.balance;
-    }
-    Ok((available_balance, locked_balance, response.share))
+    gov_kind
+        .adapter()
+        .query_staker(querier, gov_addr, contract_addr)
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
@@ -421,21 +2229,109 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> Result<Binary, ContractErr
         QueryMsg::Config {} => Ok(to_binary(&query_config(deps)?)?),
         QueryMsg::State {} => Ok(to_binary(&query_state(deps, _env)?)?),
         QueryMsg::UserState { user } => Ok(to_binary(&query_user_state(deps, _env, user)?)?),
+        QueryMsg::UserStateAtHeight { user, height } => {
+            Ok(to_binary(&query_user_state_at_height(deps, user, height)?)?)
+        }
+        QueryMsg::TotalSharesAtHeight { height } => {
+            Ok(to_binary(&query_total_shares_at_height(deps, height)?)?)
+        }
+        QueryMsg::AverageShares { user, start, end } => Ok(to_binary(&query_average_shares(
+            deps, _env, user, start, end,
+        )?)?),
+        QueryMsg::SharesAtHeight {
+            height,
+            start_after,
+            limit,
+        } => Ok(to_binary(&query_shares_at_height(
+            deps,
+            height,
+            start_after,
+            limit,
+        )?)?),
+        QueryMsg::MerkleRoot { height } => Ok(to_binary(&query_merkle_root(deps, height)?)?),
+        QueryMsg::Airdrop { token, height } => Ok(to_binary(&query_airdrop(deps, token, height)?)?),
+        QueryMsg::AirdropClaim {
+            token,
+            height,
+            user,
+        } => Ok(to_binary(&query_airdrop_claim(deps, token, height, user)?)?),
+        QueryMsg::LastCompound {} => Ok(to_binary(&query_last_compound(deps)?)?),
+        QueryMsg::DepositHeadroom { user } => {
+            Ok(to_binary(&query_deposit_headroom(deps, _env, user)?)?)
+        }
+        QueryMsg::Allowlist { start_after, limit } => {
+            Ok(to_binary(&query_allowlist(deps, start_after, limit)?)?)
+        }
+        QueryMsg::FeeExemptions { start_after, limit } => {
+            Ok(to_binary(&query_fee_exemptions(deps, start_after, limit)?)?)
+        }
+        QueryMsg::Vesting { address } => Ok(to_binary(&query_vesting(deps, _env, address)?)?),
+        QueryMsg::MetaGovConfig {} => Ok(to_binary(&query_meta_gov_config(deps)?)?),
+        QueryMsg::Proposal { proposal_id } => Ok(to_binary(&query_proposal(deps, proposal_id)?)?),
+        QueryMsg::Proposals { start_after, limit } => {
+            Ok(to_binary(&query_proposals(deps, start_after, limit)?)?)
+        }
+        QueryMsg::GovPolls { start_after, limit } => {
+            Ok(to_binary(&query_gov_polls(deps, start_after, limit)?)?)
+        }
+        QueryMsg::Hooks {} => Ok(to_binary(&query_hooks(deps)?)?),
+        QueryMsg::Health {} => Ok(to_binary(&query_health(deps, _env)?)?),
+        QueryMsg::Invariants {
+            start_after,
+            limit,
+            carry,
+        } => Ok(to_binary(&query_invariants(
+            deps,
+            _env,
+            start_after,
+            limit,
+            carry,
+        )?)?),
+        QueryMsg::Positions {
+            user,
+            start_after,
+            limit,
+        } => Ok(to_binary(&query_positions(
+            deps,
+            _env,
+            user,
+            start_after,
+            limit,
+        )?)?),
     }
 }
 fn query_config(deps: Deps) -> Result<ConfigResponse, ContractError> {
     let config: Config = config_read(deps.storage).load()?;
+    let owner = if let Some(x) = config.owner {
+        deps.api.addr_humanize(&x)?.to_string()
+    } else {
+        String::default()
+    };
     let pedding_owner = if let Some(x) = config.pendding_owner {
         deps.api.addr_humanize(&x)?.to_string()
     } else {
         String::default()
     };
+    let guardian = if let Some(x) = config.guardian {
+        deps.api.addr_humanize(&x)?.to_string()
+    } else {
+        String::default()
+    };
+    let pedding_guardian = if let Some(x) = config.pendding_guardian {
+        deps.api.addr_humanize(&x)?.to_string()
+    } else {
+        String::default()
+    };
     Ok(ConfigResponse {
-        owner: deps.api.addr_humanize(&config.owner)?.to_string(),
+        owner,
         pendding_owner: pedding_owner,
+        pendding_owner_expires: config.pendding_owner_expires,
         dev: deps.api.addr_humanize(&config.dev)?.to_string(),
+        guardian,
+        pendding_guardian: pedding_guardian,
         anchor_token: deps.api.addr_humanize(&config.anchor_token)?.to_string(),
         anchor_gov: deps.api.addr_humanize(&config.anchor_gov)?.to_string(),
+        gov_kind: config.gov_kind,
     })
 }
 /// query state of contract
@@ -443,9 +2339,18 @@ fn query_state(deps: Deps, env: Env) -> Result<StateResponse, ContractError> {
     let config: Config = config_read(deps.storage).load()?;
     let total_shares = total_shares_read(deps.storage).load()?;
     let feerate = feerate_read(deps.storage).load()?;
+    let fee_tiers = fee_tiers_read(deps.storage).load()?;
+    let paused = paused_read(deps.storage).load()?;
+    let fee_mode = fee_mode_read(deps.storage).load()?;
+    let claimable_fees = claimable_fees_read(deps.storage).load()?;
+    let stray_anc_policy = stray_anc_policy_read(deps.storage).load()?;
+    let compound_incentive = compound_incentive_read(deps.storage)
+        .may_load()?
+        .unwrap_or_default();
 
     let (available_balance, locked_balance, _) = query_balance_from_gov(
         &deps.querier,
+        &config.gov_kind,
         deps.api.addr_humanize(&config.anchor_gov)?,
         env.contract.address,
     )?;
@@ -455,6 +2360,12 @@ fn query_state(deps: Deps, env: Env) -> Result<StateResponse, ContractError> {
         feerate,
         locked_balance,
         available_balance,
+        paused,
+        fee_tiers,
+        fee_mode,
+        claimable_fees,
+        stray_anc_policy,
+        compound_incentive,
     })
 }
 
@@ -469,16 +2380,23 @@ fn query_user_state(
             locked_balance: Uint128::zero(),
             available_balance: Uint128::zero(),
             shares: Uint128::zero(),
+            seconds_to_free_exit: 0,
         });
     }
     let key = deps.api.addr_canonicalize(&user)?;
+    let seconds_to_free_exit = seconds_to_free_exit(
+        &exit_fee_read(deps.storage).load()?,
+        last_deposit_read(deps.storage).may_load(key.as_slice())?,
+        env.block.time.seconds(),
+    );
     let user_shares = user_states_read(deps.storage)
-        .may_load(&key.as_slice())?
+        .may_load(key.as_slice())?
         .unwrap_or_default();
 
     let config: Config = config_read(deps.storage).load()?;
     let (available_balance, locked_balance, _) = query_balance_from_gov(
         &deps.querier,
+        &config.gov_kind,
         deps.api.addr_humanize(&config.anchor_gov)?,
         env.contract.address,
     )?;
@@ -487,9 +2405,491 @@ fn query_user_state(
         locked_balance: locked_balance * user_shares / total_shares,
         available_balance: available_balance * user_shares / total_shares,
         shares: user_shares,
+        seconds_to_free_exit,
+    })
+}
+
+fn query_deposit_headroom(
+    deps: Deps,
+    env: Env,
+    user: Option<String>,
+) -> Result<DepositHeadroomResponse, ContractError> {
+    let limits = deposit_limits_read(deps.storage).load()?;
+    let config: Config = config_read(deps.storage).load()?;
+    let (available_balance, locked_balance, _) = query_balance_from_gov(
+        &deps.querier,
+        &config.gov_kind,
+        deps.api.addr_humanize(&config.anchor_gov)?,
+        env.contract.address,
+    )?;
+    let total_balance = available_balance
+        + locked_balance
+        + pool_poll_deposits_read(deps.storage)
+            .may_load()?
+            .unwrap_or_default();
+
+    let total_headroom = limits
+        .max_total_deposit
+        .map(|v| v.checked_sub(total_balance).unwrap_or_default());
+
+    let mut user_headroom = None;
+    if let Some(user) = user {
+        let key = deps.api.addr_canonicalize(&user)?;
+        let user_shares = user_states_read(deps.storage)
+            .may_load(key.as_slice())?
+            .unwrap_or_default();
+        let total_shares = total_shares_read(deps.storage).load()?;
+        let user_balance = shares_to_balance(user_shares, total_balance, total_shares);
+        let per_user_headroom = limits
+            .max_per_user
+            .map(|v| v.checked_sub(user_balance).unwrap_or_default());
+        user_headroom = match (total_headroom, per_user_headroom) {
+            (Some(a), Some(b)) => Some(std::cmp::min(a, b)),
+            (a, b) => a.or(b),
+        };
+    }
+
+    Ok(DepositHeadroomResponse {
+        max_total_deposit: limits.max_total_deposit,
+        max_per_user: limits.max_per_user,
+        total_headroom,
+        user_headroom,
+    })
+}
+
+fn query_allowlist(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> Result<AllowlistResponse, ContractError> {
+    let start_after = if let Some(x) = start_after {
+        Some(deps.api.addr_canonicalize(&x)?)
+    } else {
+        None
+    };
+    let addresses = read_addresses(allowlist_read(deps.storage), start_after, limit)?
+        .iter()
+        .map(|x| Ok(deps.api.addr_humanize(x)?.to_string()))
+        .collect::<StdResult<Vec<String>>>()?;
+
+    Ok(AllowlistResponse {
+        enabled: allowlist_enabled_read(deps.storage).load()?,
+        addresses,
+    })
+}
+
+fn query_fee_exemptions(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> Result<FeeExemptionsResponse, ContractError> {
+    let start_after = if let Some(x) = start_after {
+        Some(deps.api.addr_canonicalize(&x)?)
+    } else {
+        None
+    };
+    let addresses = read_addresses(fee_exemptions_read(deps.storage), start_after, limit)?
+        .iter()
+        .map(|x| Ok(deps.api.addr_humanize(x)?.to_string()))
+        .collect::<StdResult<Vec<String>>>()?;
+
+    Ok(FeeExemptionsResponse { addresses })
+}
+
+fn query_vesting(deps: Deps, env: Env, address: String) -> Result<VestingResponse, ContractError> {
+    let address_raw = deps.api.addr_canonicalize(&address)?;
+    let now = env.block.time.seconds();
+    let shares = user_states_read(deps.storage)
+        .may_load(address_raw.as_slice())?
+        .unwrap_or_default();
+    let unvested = vesting_read(deps.storage, &address_raw)
+        .range(None, None, Order::Ascending)
+        .map(|item| Ok(unvested_shares(&item?.1, now)))
+        .collect::<StdResult<Vec<Uint128>>>()?
+        .into_iter()
+        .sum::<Uint128>();
+
+    Ok(VestingResponse {
+        duration: dev_vesting_read(deps.storage).load()?,
+        vested: shares.checked_sub(unvested)?,
+        unvested,
+    })
+}
+
+fn query_meta_gov_config(deps: Deps) -> Result<MetaGovConfigResponse, ContractError> {
+    Ok(match meta_gov_config_read(deps.storage).may_load()? {
+        Some(x) => MetaGovConfigResponse {
+            enabled: true,
+            quorum: x.quorum,
+            threshold: x.threshold,
+            voting_period: x.voting_period,
+            veto_until: x.veto_until,
+            proposal_min_shares: x.proposal_min_shares,
+        },
+        None => MetaGovConfigResponse {
+            enabled: false,
+            quorum: Decimal::zero(),
+            threshold: Decimal::zero(),
+            voting_period: 0,
+            veto_until: 0,
+            proposal_min_shares: Decimal::zero(),
+        },
+    })
+}
+
+fn proposal_to_response(deps: Deps, proposal: Proposal) -> StdResult<ProposalResponse> {
+    Ok(ProposalResponse {
+        id: proposal.id,
+        proposer: deps.api.addr_humanize(&proposal.proposer)?.to_string(),
+        action: proposal.action,
+        yes_votes: proposal.yes_votes,
+        no_votes: proposal.no_votes,
+        end_time: proposal.end_time,
+        status: proposal.status,
+    })
+}
+
+fn query_proposal(deps: Deps, proposal_id: u64) -> Result<ProposalResponse, ContractError> {
+    let proposal = proposals_read(deps.storage).load(&proposal_id.to_be_bytes())?;
+    Ok(proposal_to_response(deps, proposal)?)
+}
+
+fn query_proposals(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> Result<ProposalsResponse, ContractError> {
+    let proposals = read_proposals(deps.storage, start_after, limit)?
+        .into_iter()
+        .map(|x| proposal_to_response(deps, x))
+        .collect::<StdResult<Vec<ProposalResponse>>>()?;
+
+    Ok(ProposalsResponse { proposals })
+}
+
+fn query_gov_polls(
+    deps: Deps,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> Result<GovPollsResponse, ContractError> {
+    let polls = read_gov_polls(deps.storage, start_after, limit)?
+        .into_iter()
+        .map(|x| {
+            Ok(GovPollResponse {
+                poll_id: x.poll_id,
+                deposit: x.deposit,
+                settled: x.settled,
+                funder: x
+                    .funder
+                    .map(|funder| deps.api.addr_humanize(&funder))
+                    .transpose()?
+                    .map(|funder| funder.to_string()),
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(GovPollsResponse { polls })
+}
+
+fn query_user_state_at_height(
+    deps: Deps,
+    user: String,
+    height: u64,
+) -> Result<UserStateAtHeightResponse, ContractError> {
+    let user_raw = deps.api.addr_canonicalize(user.as_str())?;
+    Ok(UserStateAtHeightResponse {
+        user,
+        height,
+        shares: user_shares_at_height(deps.storage, &user_raw, height)?,
+    })
+}
+
+fn query_total_shares_at_height(
+    deps: Deps,
+    height: u64,
+) -> Result<TotalSharesAtHeightResponse, ContractError> {
+    Ok(TotalSharesAtHeightResponse {
+        height,
+        total_shares: total_shares_at_height(deps.storage, height)?,
+    })
+}
+
+fn query_average_shares(
+    deps: Deps,
+    env: Env,
+    user: String,
+    start: u64,
+    end: u64,
+) -> Result<AverageSharesResponse, ContractError> {
+    if start >= end || end > env.block.time.seconds() {
+        return Err(ContractError::InvalidTimeRange {});
+    }
+    let user_raw = deps.api.addr_canonicalize(user.as_str())?;
+    let share_seconds = user_share_seconds(deps.storage, &user_raw, end)?
+        .checked_sub(user_share_seconds(deps.storage, &user_raw, start)?)?;
+    let total_share_seconds = total_share_seconds(deps.storage, end)?
+        .checked_sub(total_share_seconds(deps.storage, start)?)?;
+    let duration = Uint128::from(end - start);
+
+    Ok(AverageSharesResponse {
+        user,
+        start,
+        end,
+        share_seconds,
+        total_share_seconds,
+        average_shares: share_seconds.multiply_ratio(1u128, duration),
+        average_total_shares: total_share_seconds.multiply_ratio(1u128, duration),
+    })
+}
+
+fn query_shares_at_height(
+    deps: Deps,
+    height: u64,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> Result<SharesAtHeightResponse, ContractError> {
+    let start_after = match start_after {
+        Some(x) => Some(deps.api.addr_canonicalize(&x)?),
+        None => None,
+    };
+    let addresses = read_addresses(holders_read(deps.storage), start_after, limit)?;
+    let mut holders = vec![];
+    for address in addresses.iter() {
+        let shares = user_shares_at_height(deps.storage, address, height)?;
+        if !shares.is_zero() {
+            holders.push(HolderShares {
+                address: deps.api.addr_humanize(address)?.to_string(),
+                shares,
+            });
+        }
+    }
+    let mut last_user = None;
+    if let Some(x) = addresses.last() {
+        if !read_addresses(holders_read(deps.storage), Some(x.clone()), Some(1))?.is_empty() {
+            last_user = Some(deps.api.addr_humanize(x)?.to_string());
+        }
+    }
+
+    Ok(SharesAtHeightResponse {
+        height,
+        holders,
+        last_user,
+    })
+}
+
+fn query_merkle_root(deps: Deps, height: Option<u64>) -> Result<MerkleRootResponse, ContractError> {
+    let (height, root) = match height {
+        Some(x) => (x, merkle_roots_read(deps.storage).load(&x.to_be_bytes())?),
+        None => {
+            let (k, v) = merkle_roots_read(deps.storage)
+                .range(None, None, Order::Descending)
+                .next()
+                .ok_or_else(|| StdError::not_found("MerkleRoot"))??;
+            let mut height = [0u8; 8];
+            height.copy_from_slice(&k);
+            (u64::from_be_bytes(height), v)
+        }
+    };
+
+    Ok(MerkleRootResponse {
+        height,
+        merkle_root: root.merkle_root,
+        total_shares: root.total_shares,
+    })
+}
+
+fn query_airdrop(deps: Deps, token: String, height: u64) -> Result<AirdropResponse, ContractError> {
+    let token_raw = deps.api.addr_canonicalize(token.as_str())?;
+    let airdrop = airdrops_read(deps.storage, &token_raw).load(&height.to_be_bytes())?;
+    Ok(AirdropResponse {
+        token,
+        height: airdrop.height,
+        amount: airdrop.amount,
+        total_shares: airdrop.total_shares,
+        claimed: airdrop.claimed,
+    })
+}
+
+fn query_airdrop_claim(
+    deps: Deps,
+    token: String,
+    height: u64,
+    user: String,
+) -> Result<AirdropClaimResponse, ContractError> {
+    let token_raw = deps.api.addr_canonicalize(token.as_str())?;
+    let user_raw = deps.api.addr_canonicalize(user.as_str())?;
+    let airdrop = airdrops_read(deps.storage, &token_raw).load(&height.to_be_bytes())?;
+    match airdrop_claims_read(deps.storage, &token_raw, height).may_load(user_raw.as_slice())? {
+        Some(amount) => Ok(AirdropClaimResponse {
+            amount,
+            claimed: true,
+        }),
+        None => Ok(AirdropClaimResponse {
+            amount: airdrop_share(deps.storage, &airdrop, &user_raw)?,
+            claimed: false,
+        }),
+    }
+}
+
+fn query_last_compound(deps: Deps) -> Result<CompoundResponse, ContractError> {
+    let compound = last_compound_read(deps.storage).load()?;
+    Ok(CompoundResponse {
+        height: compound.height,
+        time: compound.time,
+        staked: compound.staked,
+        incentive: compound.incentive,
+        exchange_rate_before: compound.exchange_rate_before,
+        exchange_rate_after: compound.exchange_rate_after,
+    })
+}
+
+fn query_hooks(deps: Deps) -> Result<HooksResponse, ContractError> {
+    let hooks = hooks_read(deps.storage)
+        .may_load()?
+        .unwrap_or_default()
+        .iter()
+        .map(|x| {
+            Ok(HookInfo {
+                contract: deps.api.addr_humanize(&x.contract)?.to_string(),
+                fatal: x.fatal,
+            })
+        })
+        .collect::<StdResult<Vec<HookInfo>>>()?;
+    Ok(HooksResponse { hooks })
+}
+
+fn query_health(deps: Deps, env: Env) -> Result<HealthResponse, ContractError> {
+    let config: Config = config_read(deps.storage).load()?;
+    let total_shares = total_shares_read(deps.storage).load()?;
+    let checkpoint = gov_checkpoint_read(deps.storage)
+        .may_load()?
+        .unwrap_or_default();
+    let (available_balance, locked_balance, gov_share) = query_balance_from_gov(
+        &deps.querier,
+        &config.gov_kind,
+        deps.api.addr_humanize(&config.anchor_gov)?,
+        env.contract.address,
+    )?;
+    let gov_balance = available_balance + locked_balance;
+
+    let abs_diff = |a: Uint128, b: Uint128| if a > b { a - b } else { b - a };
+    let share_discrepancy = abs_diff(gov_share, checkpoint.gov_share);
+    let total_shares_discrepancy = abs_diff(total_shares, checkpoint.total_shares);
+
+    // value of the checkpoint share at the current gov exchange rate.
+    let checkpoint_share_balance = if gov_share.is_zero() {
+        Uint128::zero()
+    } else {
+        gov_balance.multiply_ratio(checkpoint.gov_share, gov_share)
+    };
+    let reward_growth = checkpoint_share_balance.saturating_sub(checkpoint.gov_balance);
+    let loss = checkpoint
+        .gov_balance
+        .saturating_sub(checkpoint_share_balance);
+
+    let balance_per_share = if total_shares.is_zero() {
+        Decimal::one()
+    } else {
+        Decimal::from_ratio(gov_balance, total_shares)
+    };
+
+    Ok(HealthResponse {
+        gov_balance,
+        gov_share,
+        total_shares,
+        checkpoint_balance: checkpoint.gov_balance,
+        checkpoint_share: checkpoint.gov_share,
+        share_discrepancy,
+        share_tolerance: checkpoint.share_tolerance,
+        total_shares_discrepancy,
+        reward_growth,
+        loss,
+        balance_per_share,
+        healthy: share_discrepancy <= checkpoint.share_tolerance
+            && total_shares_discrepancy.is_zero()
+            && loss.is_zero(),
+    })
+}
+
+fn query_invariants(
+    deps: Deps,
+    env: Env,
+    start_after: Option<String>,
+    limit: Option<u32>,
+    carry: Option<InvariantsCarry>,
+) -> Result<InvariantsResponse, ContractError> {
+    let config: Config = config_read(deps.storage).load()?;
+    let total_shares = total_shares_read(deps.storage).load()?;
+    let (available_balance, locked_balance, _) = query_balance_from_gov(
+        &deps.querier,
+        &config.gov_kind,
+        deps.api.addr_humanize(&config.anchor_gov)?,
+        env.contract.address,
+    )?;
+    let gov_balance = available_balance + locked_balance;
+
+    let start_after = match start_after {
+        Some(x) => Some(deps.api.addr_canonicalize(&x)?),
+        None => None,
+    };
+    let user_states = read_user_states(deps.storage, start_after, limit)?;
+
+    let mut carry = carry.unwrap_or_default();
+    let mut zero_share_users = vec![];
+    for (user, shares) in user_states.iter() {
+        if shares.is_zero() {
+            zero_share_users.push(deps.api.addr_humanize(user)?.to_string());
+        }
+        carry.shares += *shares;
+        // claims are valued the same way as query_user_state.
+        carry.claims += shares_to_balance(*shares, available_balance, total_shares)
+            + shares_to_balance(*shares, locked_balance, total_shares);
+    }
+
+    let last_user = user_states.last().map(|(x, _)| x.clone());
+    let finished = match &last_user {
+        Some(x) => read_user_states(deps.storage, Some(x.clone()), Some(1))?.is_empty(),
+        None => true,
+    };
+
+    Ok(InvariantsResponse {
+        total_shares,
+        gov_balance,
+        shares_match: finished && carry.shares == total_shares,
+        balance_covers_claims: gov_balance >= carry.claims,
+        carry,
+        zero_share_users,
+        last_user: match last_user {
+            Some(x) => Some(deps.api.addr_humanize(&x)?.to_string()),
+            None => None,
+        },
+        finished,
     })
 }
 
+fn query_positions(
+    deps: Deps,
+    env: Env,
+    user: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> Result<PositionsResponse, ContractError> {
+    let user_raw = deps.api.addr_canonicalize(&user)?;
+    let now = env.block.time.seconds();
+    let positions = read_positions(deps.storage, &user_raw, start_after, limit)?
+        .into_iter()
+        .map(|x| PositionResponse {
+            id: x.id,
+            shares: x.shares,
+            weight: x.weight,
+            unlock_time: x.unlock_time,
+            locked: x.unlock_time > now,
+        })
+        .collect();
+
+    Ok(PositionsResponse { positions })
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
     if msg.id == WITHDRAW_REPLY_ID {
@@ -507,7 +2907,65 @@ pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractE
         );
     }
 
+    if msg.id == CREATE_POLL_WITHDRAW_REPLY_ID {
+        let config: Config = config_read(deps.storage).load()?;
+        let poll = temp_poll_read(deps.storage).load()?;
+        let deposit = poll.deposit;
+        // the poll id is only known from the gov events.
+        return Ok(Response::new()
+            .add_submessage(create_poll_msg(&config, deps.api, poll)?)
+            .add_attribute("action", "create_poll")
+            .add_attribute("deposit", deposit.to_string()));
+    }
+
+    if msg.id == CREATE_POLL_REPLY_ID {
+        let poll = temp_poll_read(deps.storage).load()?;
+        let poll_id = parse_poll_id(msg)?;
+        gov_polls_store(deps.storage).save(
+            &poll_id.to_be_bytes(),
+            &GovPoll {
+                poll_id,
+                deposit: poll.deposit,
+                settled: false,
+                funder: poll.funder.clone(),
+            },
+        )?;
+        let outstanding = outstanding_poll_deposits_read(deps.storage)
+            .may_load()?
+            .unwrap_or_default();
+        outstanding_poll_deposits_store(deps.storage).save(&(outstanding + poll.deposit))?;
+        if poll.funder.is_none() {
+            let pool_deposits = pool_poll_deposits_read(deps.storage)
+                .may_load()?
+                .unwrap_or_default();
+            pool_poll_deposits_store(deps.storage).save(&(pool_deposits + poll.deposit))?;
+        }
+        temp_poll_store(deps.storage).remove();
+        return Ok(Response::new()
+            .add_attribute("action", "track_poll")
+            .add_attribute("poll_id", poll_id.to_string()));
+    }
+
+    if msg.id == HOOK_REPLY_ID {
+        // best-effort hooks failing must not revert the share change.
+        let err = msg.result.into_result().err().unwrap_or_default();
+        return Ok(Response::new()
+            .add_attribute("action", "hook_failed")
+            .add_attribute("error", err));
+    }
+
     Err(ContractError::Std(StdError::generic_err(
         "not supported reply",
     )))
 }
+
+fn parse_poll_id(msg: Reply) -> Result<u64, ContractError> {
+    let res = msg.result.into_result().map_err(StdError::generic_err)?;
+    res.events
+        .iter()
+        .filter(|x| x.ty == "wasm")
+        .flat_map(|x| x.attributes.iter())
+        .find(|x| x.key == "poll_id")
+        .and_then(|x| x.value.parse::<u64>().ok())
+        .ok_or(ContractError::PollIdNotFound {})
+}
@@ -12,9 +12,39 @@ pub enum ContractError {
     #[error("Unauthorized")]
     Unauthorized {},
 
+    #[error("Too many hooks")]
+    TooManyHooks {},
+
+    #[error("Invalid merkle root")]
+    InvalidMerkleRoot {},
+
+    #[error("Snapshot of the height is not final")]
+    SnapshotNotFinal {},
+
+    #[error("Merkle root of the height already exists")]
+    MerkleRootExists {},
+
+    #[error("Time range must be non-empty and not end in the future")]
+    InvalidTimeRange {},
+
+    #[error("Airdrop of the token at the height already exists")]
+    AirdropExists {},
+
+    #[error("ANC can not be airdropped")]
+    InvalidAirdropToken {},
+
+    #[error("Airdrop already claimed")]
+    AlreadyClaimed {},
+
+    #[error("Native funds are not accepted")]
+    UnexpectedFunds {},
+
     #[error("FeeRate out of limits")]
     FeeRateOutOfLimits {},
 
+    #[error("Fee tiers must have increasing amounts and non-increasing rates")]
+    InvalidFeeTiers {},
+
     #[error("Insufficient funds sent")]
     InsufficientFunds {},
 
@@ -23,6 +53,84 @@ pub enum ContractError {
 
     #[error("Nothing staked")]
     NothingStaked {},
+
+    #[error("Nothing to claim")]
+    NothingToClaim {},
+
+    #[error("Nothing to compound")]
+    NothingToCompound {},
+
+    #[error("Compound incentive out of limits")]
+    CompoundIncentiveOutOfLimits {},
+
+    #[error("Unsolicited ANC is credited to the pool")]
+    StrayAncCredited {},
+
+    #[error("Meta governance is disabled")]
+    MetaGovDisabled {},
+
+    #[error("Invalid meta governance config")]
+    InvalidMetaGovConfig {},
+
+    #[error("Proposer shares are below the minimum")]
+    ProposerSharesTooLow {},
+
+    #[error("Voting is closed")]
+    VotingClosed {},
+
+    #[error("Voting period has not ended")]
+    VotingNotEnded {},
+
+    #[error("User has already voted")]
+    AlreadyVoted {},
+
+    #[error("Veto period has expired")]
+    VetoExpired {},
+
+    #[error("Poll id not found in gov response")]
+    PollIdNotFound {},
+
+    #[error("Deposit exceeds the gov proposal deposit")]
+    DepositExceedsProposalDeposit {},
+
+    #[error("Contract is paused")]
+    Paused {},
+
+    #[error("Expiration is already expired")]
+    InvalidExpiration {},
+
+    #[error("Expiration is beyond the transfer window")]
+    ExpirationTooLong {},
+
+    #[error("Ownership transfer expired")]
+    OwnershipTransferExpired {},
+
+    #[error("Deposit exceeds max total deposit")]
+    DepositCapExceeded {},
+
+    #[error("Deposit exceeds max deposit per user")]
+    UserDepositCapExceeded {},
+
+    #[error("Sender is not in the allowlist")]
+    NotAllowlisted {},
+
+    #[error("Lock duration out of limits")]
+    LockDurationOutOfLimits {},
+
+    #[error("Duration exceeds the max duration")]
+    DurationTooLong {},
+
+    #[error("Lock boost out of limits")]
+    BoostOutOfLimits {},
+
+    #[error("Shares are locked")]
+    SharesLocked {},
+
+    #[error("Gov poll is in progress")]
+    PollInProgress {},
+
+    #[error("Gov poll is already settled")]
+    PollSettled {},
     // Add any other custom errors you like here.
     // Look at https://docs.rs/thiserror/1.0.21/thiserror/ for details.
 }
@@ -0,0 +1,373 @@
+use anchor_token::gov::{
+    ConfigResponse as AnchorConfigResponse, Cw20HookMsg as AnchorCw20HookMsg,
+    ExecuteMsg as AnchorExecuteMsg, PollExecuteMsg, PollResponse as AnchorPollResponse, PollStatus,
+    QueryMsg as AnchorQueryMsg, StakerResponse as AnchorStakerResponse,
+};
+use cosmwasm_std::{
+    to_binary, Addr, Binary, Decimal, QuerierWrapper, QueryRequest, StdError, StdResult, Uint128,
+    WasmQuery,
+};
+
+use crate::msg::GovKind;
+
+/// StakingGov builds the messages of a gov contract with the ANC style
+/// `StakeVotingTokens`, `WithdrawVotingTokens` and `Staker` api.
+pub trait StakingGov {
+    /// stake_hook is the Cw20 `Send` hook staking the sent tokens.
+    fn stake_hook(&self) -> StdResult<Binary>;
+
+    fn withdraw_msg(&self, amount: Option<Uint128>) -> StdResult<Binary>;
+
+    /// create_poll_hook is the Cw20 `Send` hook creating a poll with the sent deposit.
+    fn create_poll_hook(
+        &self,
+        title: String,
+        description: String,
+        link: Option<String>,
+        execute_msgs: Option<Vec<PollExecuteMsg>>,
+    ) -> StdResult<Binary>;
+
+    /// query_staker returns the available balance, locked balance and share of staker.
+    fn query_staker(
+        &self,
+        querier: &QuerierWrapper,
+        gov_addr: Addr,
+        staker: Addr,
+    ) -> StdResult<(Uint128, Uint128, Uint128)>;
+
+    /// query_proposal_deposit returns the deposit the gov asks for a new poll.
+    fn query_proposal_deposit(
+        &self,
+        querier: &QuerierWrapper,
+        gov_addr: Addr,
+    ) -> StdResult<Uint128>;
+
+    /// query_poll_outcome returns whether the poll ended and gov refunded its deposit.
+    fn query_poll_outcome(
+        &self,
+        querier: &QuerierWrapper,
+        gov_addr: Addr,
+        poll_id: u64,
+    ) -> StdResult<PollOutcome>;
+}
+
+/// PollOutcome is what became of the deposit of a gov poll.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PollOutcome {
+    InProgress,
+    DepositReturned,
+    DepositKept,
+}
+
+// gov refunds the deposit once the poll reached quorum, only rejected polls may have missed it.
+fn poll_outcome(
+    in_progress: bool,
+    rejected: bool,
+    tallied: Uint128,
+    staked: Option<Uint128>,
+    quorum: Decimal,
+) -> PollOutcome {
+    if in_progress {
+        return PollOutcome::InProgress;
+    }
+    let quorum_reached = match staked {
+        Some(staked) if !staked.is_zero() && !tallied.is_zero() => {
+            Decimal::from_ratio(tallied, staked) >= quorum
+        }
+        _ => false,
+    };
+    if !rejected || quorum_reached {
+        PollOutcome::DepositReturned
+    } else {
+        PollOutcome::DepositKept
+    }
+}
+
+/// GovAdapter picks the StakingGov of a GovKind.
+pub trait GovAdapter {
+    fn adapter(&self) -> &'static dyn StakingGov;
+}
+
+impl GovAdapter for GovKind {
+    fn adapter(&self) -> &'static dyn StakingGov {
+        match self {
+            GovKind::Anchor => &AnchorGov,
+            GovKind::Mirror => &MirrorGov,
+        }
+    }
+}
+
+/// AnchorGov also fits forks keeping the anchor gov messages.
+pub struct AnchorGov;
+
+impl StakingGov for AnchorGov {
+    fn stake_hook(&self) -> StdResult<Binary> {
+        to_binary(&AnchorCw20HookMsg::StakeVotingTokens {})
+    }
+
+    fn withdraw_msg(&self, amount: Option<Uint128>) -> StdResult<Binary> {
+        to_binary(&AnchorExecuteMsg::WithdrawVotingTokens { amount })
+    }
+
+    fn create_poll_hook(
+        &self,
+        title: String,
+        description: String,
+        link: Option<String>,
+        execute_msgs: Option<Vec<PollExecuteMsg>>,
+    ) -> StdResult<Binary> {
+        to_binary(&AnchorCw20HookMsg::CreatePoll {
+            title,
+            description,
+            link,
+            execute_msgs,
+        })
+    }
+
+    fn query_staker(
+        &self,
+        querier: &QuerierWrapper,
+        gov_addr: Addr,
+        staker: Addr,
+    ) -> StdResult<(Uint128, Uint128, Uint128)> {
+        let response: AnchorStakerResponse =
+            querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+                contract_addr: gov_addr.to_string(),
+                msg: to_binary(&AnchorQueryMsg::Staker {
+                    address: staker.to_string(),
+                })?,
+            }))?;
+        let locked_balance = response.locked_balance.iter().map(|(_, x)| x.balance);
+        Ok(split_balance(
+            response.balance,
+            locked_balance,
+            response.share,
+        ))
+    }
+
+    fn query_proposal_deposit(
+        &self,
+        querier: &QuerierWrapper,
+        gov_addr: Addr,
+    ) -> StdResult<Uint128> {
+        let response: AnchorConfigResponse =
+            querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+                contract_addr: gov_addr.to_string(),
+                msg: to_binary(&AnchorQueryMsg::Config {})?,
+            }))?;
+        Ok(response.proposal_deposit)
+    }
+
+    fn query_poll_outcome(
+        &self,
+        querier: &QuerierWrapper,
+        gov_addr: Addr,
+        poll_id: u64,
+    ) -> StdResult<PollOutcome> {
+        let poll: AnchorPollResponse = querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+            contract_addr: gov_addr.to_string(),
+            msg: to_binary(&AnchorQueryMsg::Poll { poll_id })?,
+        }))?;
+        let config: AnchorConfigResponse =
+            querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+                contract_addr: gov_addr.to_string(),
+                msg: to_binary(&AnchorQueryMsg::Config {})?,
+            }))?;
+        Ok(poll_outcome(
+            poll.status == PollStatus::InProgress,
+            poll.status == PollStatus::Rejected,
+            poll.yes_votes + poll.no_votes,
+            poll.staked_amount.or(poll.total_balance_at_end_poll),
+            config.quorum,
+        ))
+    }
+}
+
+/// MirrorGov polls take a single execute msg, and its voters can abstain.
+pub struct MirrorGov;
+
+impl StakingGov for MirrorGov {
+    fn stake_hook(&self) -> StdResult<Binary> {
+        to_binary(&mirror::Cw20HookMsg::StakeVotingTokens {})
+    }
+
+    fn withdraw_msg(&self, amount: Option<Uint128>) -> StdResult<Binary> {
+        to_binary(&mirror::ExecuteMsg::WithdrawVotingTokens { amount })
+    }
+
+    fn create_poll_hook(
+        &self,
+        title: String,
+        description: String,
+        link: Option<String>,
+        execute_msgs: Option<Vec<PollExecuteMsg>>,
+    ) -> StdResult<Binary> {
+        let mut execute_msgs = execute_msgs.unwrap_or_default();
+        if execute_msgs.len() > 1 {
+            return Err(StdError::generic_err(
+                "mirror gov polls take a single execute msg",
+            ));
+        }
+        to_binary(&mirror::Cw20HookMsg::CreatePoll {
+            title,
+            description,
+            link,
+            execute_msg: execute_msgs.pop().map(|x| mirror::PollExecuteMsg {
+                contract: x.contract,
+                msg: x.msg,
+            }),
+        })
+    }
+
+    fn query_staker(
+        &self,
+        querier: &QuerierWrapper,
+        gov_addr: Addr,
+        staker: Addr,
+    ) -> StdResult<(Uint128, Uint128, Uint128)> {
+        let response: mirror::StakerResponse =
+            querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+                contract_addr: gov_addr.to_string(),
+                msg: to_binary(&mirror::QueryMsg::Staker {
+                    address: staker.to_string(),
+                })?,
+            }))?;
+        let locked_balance = response.locked_balance.iter().map(|(_, x)| x.balance);
+        Ok(split_balance(
+            response.balance,
+            locked_balance,
+            response.share,
+        ))
+    }
+
+    fn query_proposal_deposit(
+        &self,
+        querier: &QuerierWrapper,
+        gov_addr: Addr,
+    ) -> StdResult<Uint128> {
+        let response: mirror::ConfigResponse =
+            querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+                contract_addr: gov_addr.to_string(),
+                msg: to_binary(&mirror::QueryMsg::Config {})?,
+            }))?;
+        Ok(response.proposal_deposit)
+    }
+
+    fn query_poll_outcome(
+        &self,
+        querier: &QuerierWrapper,
+        gov_addr: Addr,
+        poll_id: u64,
+    ) -> StdResult<PollOutcome> {
+        let poll: mirror::PollResponse = querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+            contract_addr: gov_addr.to_string(),
+            msg: to_binary(&mirror::QueryMsg::Poll { poll_id })?,
+        }))?;
+        let config: mirror::ConfigResponse =
+            querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
+                contract_addr: gov_addr.to_string(),
+                msg: to_binary(&mirror::QueryMsg::Config {})?,
+            }))?;
+        Ok(poll_outcome(
+            poll.status == "in_progress",
+            poll.status == "rejected",
+            poll.yes_votes + poll.no_votes + poll.abstain_votes,
+            poll.staked_amount.or(poll.total_balance_at_end_poll),
+            config.quorum,
+        ))
+    }
+}
+
+fn split_balance(
+    balance: Uint128,
+    locked_balances: impl Iterator<Item = Uint128>,
+    share: Uint128,
+) -> (Uint128, Uint128, Uint128) {
+    let mut available_balance = balance;
+    let mut locked_balance = Uint128::zero();
+    for x in locked_balances {
+        available_balance -= x;
+        locked_balance += x;
+    }
+    (available_balance, locked_balance, share)
+}
+
+/// mirror holds the parts of the mirror gov api used by the vault.
+pub mod mirror {
+    use cosmwasm_std::{Binary, Decimal, Uint128};
+    use schemars::JsonSchema;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+    #[serde(rename_all = "snake_case")]
+    pub enum ExecuteMsg {
+        WithdrawVotingTokens { amount: Option<Uint128> },
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+    #[serde(rename_all = "snake_case")]
+    pub enum Cw20HookMsg {
+        StakeVotingTokens {},
+        CreatePoll {
+            title: String,
+            description: String,
+            link: Option<String>,
+            execute_msg: Option<PollExecuteMsg>,
+        },
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+    #[serde(rename_all = "snake_case")]
+    pub struct PollExecuteMsg {
+        pub contract: String,
+        pub msg: Binary,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+    #[serde(rename_all = "snake_case")]
+    pub enum QueryMsg {
+        Config {},
+        Staker { address: String },
+        Poll { poll_id: u64 },
+    }
+
+    /// PollResponse keeps the status and the tally only, mirror adds `failed` and abstain votes.
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+    #[serde(rename_all = "snake_case")]
+    pub struct PollResponse {
+        pub status: String,
+        #[serde(default)]
+        pub yes_votes: Uint128,
+        #[serde(default)]
+        pub no_votes: Uint128,
+        #[serde(default)]
+        pub abstain_votes: Uint128,
+        #[serde(default)]
+        pub staked_amount: Option<Uint128>,
+        #[serde(default)]
+        pub total_balance_at_end_poll: Option<Uint128>,
+    }
+
+    /// ConfigResponse keeps the quorum and the proposal deposit only.
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+    #[serde(rename_all = "snake_case")]
+    pub struct ConfigResponse {
+        pub quorum: Decimal,
+        pub proposal_deposit: Uint128,
+    }
+
+    /// StakerResponse skips the vote of locked balances, mirror adds `abstain`.
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+    #[serde(rename_all = "snake_case")]
+    pub struct StakerResponse {
+        pub balance: Uint128,
+        pub share: Uint128,
+        pub locked_balance: Vec<(u64, LockedBalance)>,
+    }
+
+    #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+    #[serde(rename_all = "snake_case")]
+    pub struct LockedBalance {
+        pub balance: Uint128,
+    }
+}
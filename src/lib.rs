@@ -1,5 +1,7 @@
 pub mod contract;
 mod error;
+pub mod gov;
+pub mod merkle;
 pub mod msg;
 pub mod state;
 
@@ -0,0 +1,86 @@
+//! Merkle tree of shareholder balances, built off-chain from `SharesAtHeight` pages.
+//!
+//! Leaves are `sha256(address + shares)` and pairs are hashed in sorted order,
+//! the same scheme as cw20-merkle-airdrop, so its contracts can verify the proofs.
+use cosmwasm_std::{StdError, StdResult, Uint128};
+use sha2::{Digest, Sha256};
+
+use crate::msg::HolderShares;
+
+pub fn leaf(address: &str, shares: Uint128) -> [u8; 32] {
+    Sha256::digest(format!("{}{}", address, shares).as_bytes()).into()
+}
+
+fn hash_pair(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    let (first, second) = if a < b { (a, b) } else { (b, a) };
+    let mut hasher = Sha256::new();
+    hasher.update(first);
+    hasher.update(second);
+    hasher.finalize().into()
+}
+
+pub struct MerkleTree {
+    // layers[0] are the leaves, the last layer is the root
+    layers: Vec<Vec<[u8; 32]>>,
+}
+
+impl MerkleTree {
+    /// new builds the tree in the order of holders, holders with zero shares are kept.
+    pub fn new(holders: &[HolderShares]) -> StdResult<Self> {
+        if holders.is_empty() {
+            return Err(StdError::generic_err("no holders"));
+        }
+        let mut layers = vec![holders
+            .iter()
+            .map(|x| leaf(&x.address, x.shares))
+            .collect::<Vec<_>>()];
+        while layers[layers.len() - 1].len() > 1 {
+            // an odd node is carried up unchanged
+            let next = layers[layers.len() - 1]
+                .chunks(2)
+                .map(|x| match x {
+                    [a, b] => hash_pair(a, b),
+                    _ => x[0],
+                })
+                .collect();
+            layers.push(next);
+        }
+        Ok(MerkleTree { layers })
+    }
+
+    /// root returns the hex encoded root, as committed with `CommitMerkleRoot`.
+    pub fn root(&self) -> String {
+        hex::encode(self.layers[self.layers.len() - 1][0])
+    }
+
+    /// proof returns the hex encoded proof of the holder at index.
+    pub fn proof(&self, mut index: usize) -> StdResult<Vec<String>> {
+        if index >= self.layers[0].len() {
+            return Err(StdError::generic_err("index out of range"));
+        }
+        let mut proof = vec![];
+        for layer in self.layers[..self.layers.len() - 1].iter() {
+            if let Some(sibling) = layer.get(index ^ 1) {
+                proof.push(hex::encode(sibling));
+            }
+            index /= 2;
+        }
+        Ok(proof)
+    }
+}
+
+/// verify checks the proof of address holding shares against the hex encoded root.
+pub fn verify(root: &str, address: &str, shares: Uint128, proof: &[String]) -> StdResult<bool> {
+    let mut hash = leaf(address, shares);
+    for p in proof.iter() {
+        hash = hash_pair(&hash, &decode_hash(p)?);
+    }
+    Ok(hex::encode(hash) == root)
+}
+
+/// decode_hash parses a hex encoded sha256 hash.
+pub fn decode_hash(hash: &str) -> StdResult<[u8; 32]> {
+    let mut buf = [0u8; 32];
+    hex::decode_to_slice(hash, &mut buf).map_err(|e| StdError::generic_err(e.to_string()))?;
+    Ok(buf)
+}
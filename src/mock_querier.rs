@@ -1,12 +1,13 @@
 use cosmwasm_std::testing::{MockApi, MockQuerier, MockStorage, MOCK_CONTRACT_ADDR};
 use cosmwasm_std::{
-    from_binary, from_slice, to_binary, Coin, ContractResult, Empty, OwnedDeps, Querier,
+    from_binary, from_slice, to_binary, Coin, ContractResult, Decimal, Empty, OwnedDeps, Querier,
     QuerierResult, QueryRequest, SystemError, SystemResult, Uint128, WasmQuery,
 };
 use cw20::{BalanceResponse as Cw20BalanceResponse, Cw20QueryMsg};
 use std::collections::HashMap;
 
-use anchor_token::gov::{QueryMsg, StakerResponse};
+use anchor_token::gov::{ConfigResponse, PollResponse, PollStatus, QueryMsg, StakerResponse};
+use serde::{Deserialize, Serialize};
 
 /// mock_dependencies is a drop-in replacement for cosmwasm_std::testing::mock_dependencies
 /// this uses our CustomQuerier.
@@ -27,6 +28,9 @@ pub struct WasmMockQuerier {
     base: MockQuerier<Empty>,
     token_querier: TokenQuerier,
     gov_querier: GovQuerier,
+    proposal_deposit: Uint128,
+    poll_statuses: HashMap<u64, PollStatus>,
+    poll_tallies: HashMap<u64, (Uint128, Uint128)>,
 }
 
 #[derive(Clone, Default)]
@@ -58,26 +62,59 @@ pub(crate) fn balances_to_map(
     balances_map
 }
 
+/// MirrorStakerResponse is the staker response of mirror gov.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct MirrorStakerResponse {
+    pub balance: Uint128,
+    pub share: Uint128,
+    pub locked_balance: Vec<(u64, MirrorVoterInfo)>,
+    pub pending_voting_rewards: Uint128,
+    pub withdrawable_polls: Vec<(u64, Uint128)>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct MirrorVoterInfo {
+    pub vote: MirrorVoteOption,
+    pub balance: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum MirrorVoteOption {
+    Yes,
+    No,
+    Abstain,
+}
+
 #[derive(Clone, Default)]
 pub struct GovQuerier {
     // this lets us iterate over all pairs that match the first string
     staker_infos: HashMap<String, HashMap<String, StakerResponse>>,
+    mirror_staker_infos: HashMap<String, HashMap<String, MirrorStakerResponse>>,
 }
 
 impl GovQuerier {
     pub fn new(staker_infos: &[(&String, &[(&String, &StakerResponse)])]) -> Self {
         GovQuerier {
             staker_infos: stakers_to_map(staker_infos),
+            mirror_staker_infos: HashMap::new(),
+        }
+    }
+
+    pub fn new_mirror(staker_infos: &[(&String, &[(&String, &MirrorStakerResponse)])]) -> Self {
+        GovQuerier {
+            staker_infos: HashMap::new(),
+            mirror_staker_infos: stakers_to_map(staker_infos),
         }
     }
 }
 
-pub(crate) fn stakers_to_map(
-    staker_infos: &[(&String, &[(&String, &StakerResponse)])],
-) -> HashMap<String, HashMap<String, StakerResponse>> {
-    let mut stakers_map: HashMap<String, HashMap<String, StakerResponse>> = HashMap::new();
+pub(crate) fn stakers_to_map<T: Clone>(
+    staker_infos: &[(&String, &[(&String, &T)])],
+) -> HashMap<String, HashMap<String, T>> {
+    let mut stakers_map: HashMap<String, HashMap<String, T>> = HashMap::new();
     for (contract_addr, stakers) in staker_infos.iter() {
-        let mut contract_stakers_map: HashMap<String, StakerResponse> = HashMap::new();
+        let mut contract_stakers_map: HashMap<String, T> = HashMap::new();
         for (addr, staker) in stakers.iter() {
             contract_stakers_map.insert(addr.to_string(), (**staker).clone());
         }
@@ -139,7 +176,61 @@ impl WasmMockQuerier {
                     ))
                 }
                 _ => match from_binary(msg) {
+                    Ok(QueryMsg::Config {}) => SystemResult::Ok(ContractResult::Ok(
+                        to_binary(&ConfigResponse {
+                            owner: String::default(),
+                            anchor_token: String::default(),
+                            quorum: Decimal::percent(10),
+                            threshold: Decimal::percent(50),
+                            voting_period: 100,
+                            timelock_period: 10,
+                            expiration_period: 100,
+                            proposal_deposit: self.proposal_deposit,
+                            snapshot_period: 10,
+                        })
+                        .unwrap(),
+                    )),
+                    Ok(QueryMsg::Poll { poll_id }) => SystemResult::Ok(ContractResult::Ok(
+                        to_binary(&PollResponse {
+                            id: poll_id,
+                            creator: String::default(),
+                            status: self
+                                .poll_statuses
+                                .get(&poll_id)
+                                .cloned()
+                                .unwrap_or(PollStatus::InProgress),
+                            end_height: 0,
+                            title: String::default(),
+                            description: String::default(),
+                            link: None,
+                            deposit_amount: self.proposal_deposit,
+                            execute_data: None,
+                            yes_votes: self
+                                .poll_tallies
+                                .get(&poll_id)
+                                .map(|x| x.0)
+                                .unwrap_or_default(),
+                            no_votes: Uint128::zero(),
+                            staked_amount: self.poll_tallies.get(&poll_id).map(|x| x.1),
+                            total_balance_at_end_poll: None,
+                        })
+                        .unwrap(),
+                    )),
                     Ok(QueryMsg::Staker { address }) => {
+                        if let Some(staker_info) =
+                            self.gov_querier.mirror_staker_infos.get(contract_addr)
+                        {
+                            let v = staker_info.get(&address).cloned().unwrap_or(
+                                MirrorStakerResponse {
+                                    balance: Uint128::zero(),
+                                    share: Uint128::zero(),
+                                    locked_balance: vec![],
+                                    pending_voting_rewards: Uint128::zero(),
+                                    withdrawable_polls: vec![],
+                                },
+                            );
+                            return SystemResult::Ok(ContractResult::Ok(to_binary(&v).unwrap()));
+                        }
                         let staker_info = match self.gov_querier.staker_infos.get(contract_addr) {
                             Some(staker_info) => staker_info,
                             None => {
@@ -154,22 +245,16 @@ impl WasmMockQuerier {
                         };
 
                         match staker_info.get(&address) {
-                            Some(v) => {
-                                return SystemResult::Ok(ContractResult::Ok(
-                                    to_binary(&v).unwrap(),
-                                ));
-                            }
-                            None => {
-                                return SystemResult::Ok(ContractResult::Ok(
-                                    to_binary(&StakerResponse {
-                                        balance: Uint128::zero(),
-                                        share: Uint128::zero(),
-                                        locked_balance: vec![],
-                                    })
-                                    .unwrap(),
-                                ));
-                            }
-                        };
+                            Some(v) => SystemResult::Ok(ContractResult::Ok(to_binary(&v).unwrap())),
+                            None => SystemResult::Ok(ContractResult::Ok(
+                                to_binary(&StakerResponse {
+                                    balance: Uint128::zero(),
+                                    share: Uint128::zero(),
+                                    locked_balance: vec![],
+                                })
+                                .unwrap(),
+                            )),
+                        }
                     }
                     _ => panic!("DO NOT ENTER HERE"),
                 },
@@ -185,6 +270,9 @@ impl WasmMockQuerier {
             base,
             token_querier: TokenQuerier::default(),
             gov_querier: GovQuerier::default(),
+            proposal_deposit: Uint128::from(100u128),
+            poll_statuses: HashMap::new(),
+            poll_tallies: HashMap::new(),
         }
     }
 
@@ -196,4 +284,23 @@ impl WasmMockQuerier {
     pub fn with_gov_stakers(&mut self, stakers_info: &[(&String, &[(&String, &StakerResponse)])]) {
         self.gov_querier = GovQuerier::new(stakers_info);
     }
+
+    pub fn with_proposal_deposit(&mut self, proposal_deposit: Uint128) {
+        self.proposal_deposit = proposal_deposit;
+    }
+
+    pub fn with_poll_status(&mut self, poll_id: u64, status: PollStatus) {
+        self.poll_statuses.insert(poll_id, status);
+    }
+
+    pub fn with_poll_tally(&mut self, poll_id: u64, tallied: Uint128, staked_amount: Uint128) {
+        self.poll_tallies.insert(poll_id, (tallied, staked_amount));
+    }
+
+    pub fn with_mirror_gov_stakers(
+        &mut self,
+        stakers_info: &[(&String, &[(&String, &MirrorStakerResponse)])],
+    ) {
+        self.gov_querier = GovQuerier::new_mirror(stakers_info);
+    }
 }
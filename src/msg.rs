@@ -1,28 +1,233 @@
+use anchor_token::gov::PollExecuteMsg;
 use cosmwasm_std::{Decimal, Uint128};
-use cw20::Cw20ReceiveMsg;
+use cw20::{Cw20ReceiveMsg, Expiration};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+pub use staking_anchor_gov_msg::{GovKind, InstantiateMsg};
 
 pub const WITHDRAW_REPLY_ID: u64 = 1;
+pub const CREATE_POLL_WITHDRAW_REPLY_ID: u64 = 2;
+pub const CREATE_POLL_REPLY_ID: u64 = 3;
+pub const HOOK_REPLY_ID: u64 = 4;
 
+/// FeeTier applies `feerate` to deposits of at least `min_amount`.
+/// deposits below the first tier pay the base feerate.
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
-pub struct InstantiateMsg {
+pub struct FeeTier {
+    pub min_amount: Uint128,
     pub feerate: Decimal,
-    pub anchor_gov: String,
-    pub anchor_token: String,
-    pub dev: String,
+}
+
+/// HookInfo is a contract notified with `HookMsg` after shares change,
+/// a `fatal` hook failing reverts the deposit or withdrawal, others are best-effort.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct HookInfo {
+    pub contract: String,
+    pub fatal: bool,
+}
+
+/// FeeMode decides how the dev fee is paid, `Shares` mints pool shares to dev,
+/// `Claimable` keeps the fee as ANC outside of gov until dev claims it.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FeeMode {
+    Shares,
+    Claimable,
+}
+
+/// StrayAncPolicy decides what happens to ANC transferred to the contract without `Send`,
+/// `CreditPool` stakes it with the next deposit, `Refund` keeps it recoverable by owner.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum StrayAncPolicy {
+    CreditPool,
+    Refund,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TokenInfo {
+    Token { contract_addr: String },
+    NativeToken { denom: String },
+}
+
+/// ProposalAction is the vault parameter change a passed proposal executes.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ProposalAction {
+    UpdateFeeRate {
+        new_feerate: Decimal,
+    },
+    UpdateDev {
+        new_dev: String,
+    },
+    SetPaused {
+        paused: bool,
+    },
+    /// CreateGovPoll withdraws `deposit` from the vault stake, so it can not exceed
+    /// the gov `proposal_deposit`.
+    CreateGovPoll {
+        title: String,
+        description: String,
+        link: Option<String>,
+        execute_msgs: Option<Vec<PollExecuteMsg>>,
+        deposit: Uint128,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ProposalVote {
+    Yes,
+    No,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ProposalStatus {
+    InProgress,
+    Passed,
+    Rejected,
+    Vetoed,
+    /// Failed proposals passed, but their action was no longer valid at the tally.
+    Failed,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ExecuteMsg {
     Receive(Cw20ReceiveMsg),
-    TransferOwnerShip { new_owner: String },
+    /// TransferOwnerShip sets the pending owner, expires is required unless new_owner is empty,
+    /// and must be within `MAX_TRANSFER_SECONDS` or `MAX_TRANSFER_BLOCKS`.
+    TransferOwnerShip {
+        new_owner: String,
+        expires: Option<Expiration>,
+    },
     AcceptOwner {},
-    UpdateDev { new_dev: String },
-    UpdateFeeRate { new_feerate: Decimal },
-    WithdrawToken { amount: Option<Uint128> },
+    RenounceOwnership {},
+    TransferGuardian {
+        new_guardian: String,
+    },
+    AcceptGuardian {},
+    /// RemoveGuardian drops the guardian and the pending guardian, for a compromised key.
+    RemoveGuardian {},
+    Pause {},
+    Unpause {},
+    UpdateDev {
+        new_dev: String,
+    },
+    UpdateFeeRate {
+        new_feerate: Decimal,
+    },
+    UpdateFeeTiers {
+        tiers: Vec<FeeTier>,
+    },
+    UpdateFeeMode {
+        fee_mode: FeeMode,
+    },
+    ClaimFees {},
+    UpdateStrayAncPolicy {
+        policy: StrayAncPolicy,
+    },
+    RecoverToken {
+        token: TokenInfo,
+        amount: Uint128,
+        recipient: String,
+    },
+    SweepNative {
+        recipient: String,
+    },
+    /// UpdateMetaGovConfig needs a non-zero quorum, a threshold of at least 50%,
+    /// and proposers to hold at least `proposal_min_shares` of the total shares.
+    UpdateMetaGovConfig {
+        quorum: Decimal,
+        threshold: Decimal,
+        voting_period: u64,
+        veto_until: u64,
+        proposal_min_shares: Decimal,
+    },
+    CreateProposal {
+        action: ProposalAction,
+    },
+    CastVote {
+        proposal_id: u64,
+        vote: ProposalVote,
+    },
+    EndProposal {
+        proposal_id: u64,
+    },
+    VetoProposal {
+        proposal_id: u64,
+    },
+    /// UpdateExitFee sets the exit fee decaying over duration, at most `MAX_EXIT_FEE_DURATION`.
+    UpdateExitFee {
+        max_feerate: Decimal,
+        duration: u64,
+    },
+    /// UpdateDevVesting sets how long dev fee shares vest, at most `MAX_VESTING_DURATION`.
+    UpdateDevVesting {
+        duration: u64,
+    },
+    /// UpdateLockConfig bounds lockups, `max_lock_duration` is at most `MAX_LOCK_DURATION`
+    /// and `max_boost` at most `MAX_BOOST` percent.
+    UpdateLockConfig {
+        max_lock_duration: u64,
+        max_boost: Decimal,
+    },
+    UpdateDepositLimits {
+        max_total_deposit: Option<Uint128>,
+        max_per_user: Option<Uint128>,
+    },
+    SetAllowlistEnabled {
+        enabled: bool,
+    },
+    UpdateAllowlist {
+        add: Vec<String>,
+        remove: Vec<String>,
+    },
+    UpdateFeeExemptions {
+        add: Vec<String>,
+        remove: Vec<String>,
+    },
+    /// CommitMerkleRoot records the root of the `SharesAtHeight` holders at height,
+    /// built with `merkle::MerkleTree`. an existing root is only overwritten with `replace`.
+    CommitMerkleRoot {
+        height: u64,
+        merkle_root: String,
+        replace: Option<bool>,
+    },
+    /// RegisterAirdrop splits amount of the token held by the vault
+    /// pro-rata to the shares at the beginning of the current height.
+    /// a token can be airdropped again at a later height.
+    RegisterAirdrop {
+        token: String,
+        amount: Uint128,
+    },
+    ClaimAirdrop {
+        token: String,
+        height: u64,
+    },
+    /// Compound stakes the idle ANC of the vault into gov, anyone can call it
+    /// and receives `compound_incentive` of the amount.
+    Compound {},
+    /// SettleGovPoll stakes the deposit returned by an ended gov poll back into the pool,
+    /// anyone can call it.
+    SettleGovPoll {
+        poll_id: u64,
+    },
+    UpdateCompoundIncentive {
+        rate: Decimal,
+    },
+    /// UpdateHooks adds or updates the hooks in `add` and removes the contracts in `remove`.
+    UpdateHooks {
+        add: Vec<HookInfo>,
+        remove: Vec<String>,
+    },
+    WithdrawToken {
+        amount: Option<Uint128>,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -30,7 +235,93 @@ pub enum ExecuteMsg {
 pub enum QueryMsg {
     Config {},
     State {},
-    UserState { user: String },
+    UserState {
+        user: String,
+    },
+    /// UserStateAtHeight returns the shares of user at the beginning of height.
+    UserStateAtHeight {
+        user: String,
+        height: u64,
+    },
+    /// TotalSharesAtHeight returns total_shares at the beginning of height.
+    TotalSharesAtHeight {
+        height: u64,
+    },
+    /// AverageShares returns the time weighted average shares of user and of total_shares
+    /// between start and end seconds, share seconds accrue from the first change on.
+    AverageShares {
+        user: String,
+        start: u64,
+        end: u64,
+    },
+    /// SharesAtHeight pages through every address ever holding shares,
+    /// returning the non-zero shares at the beginning of height.
+    SharesAtHeight {
+        height: u64,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// MerkleRoot returns the root committed at height, or the latest one.
+    MerkleRoot {
+        height: Option<u64>,
+    },
+    Airdrop {
+        token: String,
+        height: u64,
+    },
+    AirdropClaim {
+        token: String,
+        height: u64,
+        user: String,
+    },
+    LastCompound {},
+    DepositHeadroom {
+        user: Option<String>,
+    },
+    Allowlist {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    FeeExemptions {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    Vesting {
+        address: String,
+    },
+    Positions {
+        user: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    MetaGovConfig {},
+    Proposal {
+        proposal_id: u64,
+    },
+    Proposals {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    GovPolls {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    Health {},
+    Hooks {},
+    /// Invariants checks user_states page by page, pass the returned `carry` to the next page.
+    Invariants {
+        start_after: Option<String>,
+        limit: Option<u32>,
+        carry: Option<InvariantsCarry>,
+    },
+}
+
+/// InvariantsCarry accumulates the shares and balance claims of the pages already checked.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+#[serde(rename_all = "snake_case")]
+pub struct InvariantsCarry {
+    pub shares: Uint128,
+    pub claims: Uint128,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -38,9 +329,13 @@ pub enum QueryMsg {
 pub struct ConfigResponse {
     pub owner: String,
     pub pendding_owner: String,
+    pub pendding_owner_expires: Option<Expiration>,
     pub dev: String,
+    pub guardian: String,
+    pub pendding_guardian: String,
     pub anchor_token: String,
     pub anchor_gov: String,
+    pub gov_kind: GovKind,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -50,6 +345,12 @@ pub struct StateResponse {
     pub locked_balance: Uint128,
     pub available_balance: Uint128,
     pub total_shares: Uint128,
+    pub paused: bool,
+    pub fee_tiers: Vec<FeeTier>,
+    pub fee_mode: FeeMode,
+    pub claimable_fees: Uint128,
+    pub stray_anc_policy: StrayAncPolicy,
+    pub compound_incentive: Decimal,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -58,10 +359,258 @@ pub struct UserStateResponse {
     pub locked_balance: Uint128,
     pub available_balance: Uint128,
     pub shares: Uint128,
+    pub seconds_to_free_exit: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct UserStateAtHeightResponse {
+    pub user: String,
+    pub height: u64,
+    pub shares: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct TotalSharesAtHeightResponse {
+    pub height: u64,
+    pub total_shares: Uint128,
+}
+
+/// AverageSharesResponse also returns the share seconds, the averages are rounded down.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct AverageSharesResponse {
+    pub user: String,
+    pub start: u64,
+    pub end: u64,
+    pub share_seconds: Uint128,
+    pub total_share_seconds: Uint128,
+    pub average_shares: Uint128,
+    pub average_total_shares: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct HolderShares {
+    pub address: String,
+    pub shares: Uint128,
+}
+
+/// SharesAtHeightResponse continues from `last_user`, it is `None` when finished.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct SharesAtHeightResponse {
+    pub height: u64,
+    pub holders: Vec<HolderShares>,
+    pub last_user: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct MerkleRootResponse {
+    pub height: u64,
+    pub merkle_root: String,
+    pub total_shares: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct AirdropResponse {
+    pub token: String,
+    pub height: u64,
+    pub amount: Uint128,
+    pub total_shares: Uint128,
+    pub claimed: Uint128,
+}
+
+/// AirdropClaimResponse returns the pro-rata amount of user, and whether it is claimed.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct AirdropClaimResponse {
+    pub amount: Uint128,
+    pub claimed: bool,
+}
+
+/// CompoundResponse returns the last `Compound`, exchange rates are gov balance per share.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct CompoundResponse {
+    pub height: u64,
+    pub time: u64,
+    pub staked: Uint128,
+    pub incentive: Uint128,
+    pub exchange_rate_before: Decimal,
+    pub exchange_rate_after: Decimal,
+}
+
+/// DepositHeadroomResponse returns how much can still be deposited, `None` means unlimited.
+/// `user_headroom` takes both the global and the per user limit into account.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct DepositHeadroomResponse {
+    pub max_total_deposit: Option<Uint128>,
+    pub max_per_user: Option<Uint128>,
+    pub total_headroom: Option<Uint128>,
+    pub user_headroom: Option<Uint128>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct AllowlistResponse {
+    pub enabled: bool,
+    pub addresses: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct FeeExemptionsResponse {
+    pub addresses: Vec<String>,
+}
+
+/// VestingResponse splits the dev fee shares of an address that are still vesting.
+/// `vested` is the rest of its shares.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct VestingResponse {
+    pub duration: u64,
+    pub vested: Uint128,
+    pub unvested: Uint128,
+}
+
+/// MetaGovConfigResponse is empty while meta governance is disabled.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct MetaGovConfigResponse {
+    pub enabled: bool,
+    pub quorum: Decimal,
+    pub threshold: Decimal,
+    pub voting_period: u64,
+    pub veto_until: u64,
+    pub proposal_min_shares: Decimal,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct ProposalResponse {
+    pub id: u64,
+    pub proposer: String,
+    pub action: ProposalAction,
+    pub yes_votes: Uint128,
+    pub no_votes: Uint128,
+    pub end_time: u64,
+    pub status: ProposalStatus,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct ProposalsResponse {
+    pub proposals: Vec<ProposalResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct GovPollResponse {
+    pub poll_id: u64,
+    pub deposit: Uint128,
+    pub settled: bool,
+    pub funder: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct GovPollsResponse {
+    pub polls: Vec<GovPollResponse>,
+}
+
+/// HealthResponse compares the gov stake with the checkpoint written by the last vault operation.
+/// `share_discrepancy` should stay within `share_tolerance`, the rounding of the last gov
+/// conversion, and `total_shares_discrepancy` should be zero, gov share only moves with
+/// vault deposits and withdrawals. `reward_growth` and `loss` are the balance change of the
+/// checkpoint share since then, from reward deposits or slashing.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct HealthResponse {
+    pub gov_balance: Uint128,
+    pub gov_share: Uint128,
+    pub total_shares: Uint128,
+    pub checkpoint_balance: Uint128,
+    pub checkpoint_share: Uint128,
+    pub share_discrepancy: Uint128,
+    pub share_tolerance: Uint128,
+    pub total_shares_discrepancy: Uint128,
+    pub reward_growth: Uint128,
+    pub loss: Uint128,
+    pub balance_per_share: Decimal,
+    pub healthy: bool,
+}
+
+/// InvariantsResponse reports one page of the accounting check.
+/// `shares_match` is only decided on the last page, when `finished` is true.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct InvariantsResponse {
+    pub total_shares: Uint128,
+    pub gov_balance: Uint128,
+    pub carry: InvariantsCarry,
+    pub zero_share_users: Vec<String>,
+    pub last_user: Option<String>,
+    pub finished: bool,
+    pub shares_match: bool,
+    pub balance_covers_claims: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct HooksResponse {
+    pub hooks: Vec<HookInfo>,
+}
+
+/// HookMsg is sent to the hooks after shares of `user` change, `shares` is the new balance.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum HookMsg {
+    SharesChanged {
+        user: String,
+        delta: ShareDelta,
+        shares: Uint128,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ShareDelta {
+    Increase { amount: Uint128 },
+    Decrease { amount: Uint128 },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum Cw20HookMsg {
-    StakingTokens {},
+    /// lock_duration in seconds opts into a lockup position with boosted weight
+    StakingTokens { lock_duration: Option<u64> },
+    /// CreateGovPoll creates a gov poll with the sent ANC as deposit, owner only.
+    /// the deposit is never taken from the vault stake.
+    CreateGovPoll {
+        title: String,
+        description: String,
+        link: Option<String>,
+        execute_msgs: Option<Vec<PollExecuteMsg>>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct PositionResponse {
+    pub id: u64,
+    pub shares: Uint128,
+    pub weight: Uint128,
+    pub unlock_time: u64,
+    pub locked: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct PositionsResponse {
+    pub positions: Vec<PositionResponse>,
 }
@@ -1,24 +1,81 @@
-use cosmwasm_std::{CanonicalAddr, Decimal, Storage, Uint128};
+use anchor_token::gov::PollExecuteMsg;
+use cosmwasm_std::{CanonicalAddr, Decimal, Order, StdResult, Storage, Uint128};
 use cosmwasm_storage::{
     bucket, bucket_read, singleton, singleton_read, Bucket, ReadonlyBucket, ReadonlySingleton,
     Singleton,
 };
+use cw20::Expiration;
+
+use crate::msg::{
+    FeeMode, FeeTier, GovKind, ProposalAction, ProposalStatus, ProposalVote, StrayAncPolicy,
+};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 static KEY_CONFIG: &[u8] = b"config";
 static KEY_FEERATE: &[u8] = b"feerate";
+static KEY_FEE_TIERS: &[u8] = b"fee_tiers";
 static KEY_TOTAL_SHARES: &[u8] = b"total_shares";
 static KEY_USER_STATES: &[u8] = b"user_states";
 static KEY_TEMP_SEND: &[u8] = b"temp_send";
+static KEY_PAUSED: &[u8] = b"paused";
+static KEY_DEPOSIT_LIMITS: &[u8] = b"deposit_limits";
+static KEY_ALLOWLIST_ENABLED: &[u8] = b"allowlist_enabled";
+static KEY_ALLOWLIST: &[u8] = b"allowlist";
+static KEY_FEE_EXEMPTIONS: &[u8] = b"fee_exemptions";
+static KEY_EXIT_FEE: &[u8] = b"exit_fee";
+static KEY_LAST_DEPOSIT: &[u8] = b"last_deposit";
+static KEY_LOCK_CONFIG: &[u8] = b"lock_config";
+static KEY_POSITION_COUNT: &[u8] = b"position_count";
+static KEY_POSITIONS: &[u8] = b"positions";
+static KEY_DEV_VESTING: &[u8] = b"dev_vesting";
+static KEY_FEE_MODE: &[u8] = b"fee_mode";
+static KEY_CLAIMABLE_FEES: &[u8] = b"claimable_fees";
+static KEY_STRAY_ANC_POLICY: &[u8] = b"stray_anc_policy";
+static KEY_META_GOV_CONFIG: &[u8] = b"meta_gov_config";
+static KEY_PROPOSAL_COUNT: &[u8] = b"proposal_count";
+static KEY_PROPOSALS: &[u8] = b"proposals";
+static KEY_VOTES: &[u8] = b"votes";
+static KEY_VOTE_LOCKS: &[u8] = b"vote_locks";
+static KEY_TEMP_POLL: &[u8] = b"temp_poll";
+static KEY_GOV_POLLS: &[u8] = b"gov_polls";
+static KEY_OUTSTANDING_POLL_DEPOSITS: &[u8] = b"outstanding_poll_deposits";
+static KEY_POOL_POLL_DEPOSITS: &[u8] = b"pool_poll_deposits";
+static KEY_GOV_CHECKPOINT: &[u8] = b"gov_checkpoint";
+static KEY_VESTING: &[u8] = b"vesting";
+static KEY_HOOKS: &[u8] = b"hooks";
+static KEY_USER_SHARES_CHANGELOG: &[u8] = b"user_shares_changelog";
+static KEY_TOTAL_SHARES_CHANGELOG: &[u8] = b"total_shares_changelog";
+static KEY_HOLDERS: &[u8] = b"holders";
+static KEY_MERKLE_ROOTS: &[u8] = b"merkle_roots";
+static KEY_AIRDROPS: &[u8] = b"airdrops";
+static KEY_AIRDROP_CLAIMS: &[u8] = b"airdrop_claims";
+static KEY_COMPOUND_INCENTIVE: &[u8] = b"compound_incentive";
+static KEY_LAST_COMPOUND: &[u8] = b"last_compound";
+static KEY_USER_SHARE_SECONDS: &[u8] = b"user_share_seconds";
+static KEY_TOTAL_SHARE_SECONDS: &[u8] = b"total_share_seconds";
+
+// settings for pagination
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Config {
-    pub owner: CanonicalAddr,
+    pub owner: Option<CanonicalAddr>,
     pub pendding_owner: Option<CanonicalAddr>,
+    pub pendding_owner_expires: Option<Expiration>,
     pub dev: CanonicalAddr,
+    pub guardian: Option<CanonicalAddr>,
+    pub pendding_guardian: Option<CanonicalAddr>,
     pub anchor_token: CanonicalAddr,
     pub anchor_gov: CanonicalAddr,
+    #[serde(default = "default_gov_kind")]
+    pub gov_kind: GovKind,
+}
+
+// configs saved before gov adapters were added are anchor gov.
+fn default_gov_kind() -> GovKind {
+    GovKind::Anchor
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -27,41 +84,796 @@ pub struct TempSendData {
     pub amount: Uint128,
 }
 
-pub fn config_store(storage: &mut dyn Storage) -> Singleton<Config> {
+/// DepositLimits caps deposits, `None` means unlimited.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct DepositLimits {
+    pub max_total_deposit: Option<Uint128>,
+    pub max_per_user: Option<Uint128>,
+}
+
+/// ExitFee charges `max_feerate` on withdrawals right after a deposit,
+/// decaying linearly to zero over `duration` seconds.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct ExitFee {
+    pub max_feerate: Decimal,
+    pub duration: u64,
+}
+
+/// LockConfig bounds lockup deposits, a lock of `max_lock_duration` seconds
+/// credits `max_boost` extra weight per share. zero duration disables locking.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct LockConfig {
+    pub max_lock_duration: u64,
+    pub max_boost: Decimal,
+}
+
+/// Position is a lockup deposit, its shares can not be withdrawn before `unlock_time`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Position {
+    pub id: u64,
+    pub shares: Uint128,
+    pub weight: Uint128,
+    pub unlock_time: u64,
+}
+
+/// VestingSchedule releases `shares` linearly between `start` and `end`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct VestingSchedule {
+    pub shares: Uint128,
+    pub start: u64,
+    pub end: u64,
+}
+
+/// MetaGovConfig lets share holders vote on vault parameters,
+/// the owner can veto proposals until `veto_until`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MetaGovConfig {
+    pub quorum: Decimal,
+    pub threshold: Decimal,
+    pub voting_period: u64,
+    pub veto_until: u64,
+    #[serde(default)]
+    pub proposal_min_shares: Decimal,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Proposal {
+    pub id: u64,
+    pub proposer: CanonicalAddr,
+    pub action: ProposalAction,
+    pub yes_votes: Uint128,
+    pub no_votes: Uint128,
+    pub end_time: u64,
+    pub status: ProposalStatus,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Vote {
+    pub vote: ProposalVote,
+    pub shares: Uint128,
+    #[serde(default)]
+    pub weight: Uint128,
+}
+
+/// VoteLock keeps the voted shares of a user from being withdrawn until `end_time`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct VoteLock {
+    pub shares: Uint128,
+    pub end_time: u64,
+}
+
+/// TempPollData keeps a gov poll between withdrawing its deposit and creating it.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TempPollData {
+    pub title: String,
+    pub description: String,
+    pub link: Option<String>,
+    pub execute_msgs: Option<Vec<PollExecuteMsg>>,
+    pub deposit: Uint128,
+    #[serde(default)]
+    pub funder: Option<CanonicalAddr>,
+}
+
+/// GovPoll is a poll created by the vault, its deposit is outstanding until the poll is settled.
+/// `funder` is the owner who paid the deposit, none when the pool paid it for a meta-gov proposal.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct GovPoll {
+    pub poll_id: u64,
+    pub deposit: Uint128,
+    #[serde(default)]
+    pub settled: bool,
+    #[serde(default)]
+    pub funder: Option<CanonicalAddr>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Hook {
+    pub contract: CanonicalAddr,
+    pub fatal: bool,
+}
+
+/// GovCheckpoint is the gov stake expected right after the last vault operation.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct GovCheckpoint {
+    pub gov_balance: Uint128,
+    pub gov_share: Uint128,
+    pub total_shares: Uint128,
+    /// share_tolerance bounds the rounding of `gov_share` against the gov conversion.
+    #[serde(default)]
+    pub share_tolerance: Uint128,
+}
+
+pub fn config_store(storage: &mut dyn Storage) -> Singleton<'_, Config> {
     singleton(storage, KEY_CONFIG)
 }
 
-pub fn config_read(storage: &dyn Storage) -> ReadonlySingleton<Config> {
+pub fn config_read(storage: &dyn Storage) -> ReadonlySingleton<'_, Config> {
     singleton_read(storage, KEY_CONFIG)
 }
-pub fn feerate_store(storage: &mut dyn Storage) -> Singleton<Decimal> {
+pub fn feerate_store(storage: &mut dyn Storage) -> Singleton<'_, Decimal> {
     singleton(storage, KEY_FEERATE)
 }
 
-pub fn feerate_read(storage: &dyn Storage) -> ReadonlySingleton<Decimal> {
+pub fn feerate_read(storage: &dyn Storage) -> ReadonlySingleton<'_, Decimal> {
     singleton_read(storage, KEY_FEERATE)
 }
 
-pub fn total_shares_store(storage: &mut dyn Storage) -> Singleton<Uint128> {
+pub fn fee_tiers_store(storage: &mut dyn Storage) -> Singleton<'_, Vec<FeeTier>> {
+    singleton(storage, KEY_FEE_TIERS)
+}
+
+pub fn fee_tiers_read(storage: &dyn Storage) -> ReadonlySingleton<'_, Vec<FeeTier>> {
+    singleton_read(storage, KEY_FEE_TIERS)
+}
+
+pub fn total_shares_store(storage: &mut dyn Storage) -> Singleton<'_, Uint128> {
     singleton(storage, KEY_TOTAL_SHARES)
 }
 
-pub fn total_shares_read(storage: &dyn Storage) -> ReadonlySingleton<Uint128> {
+pub fn total_shares_read(storage: &dyn Storage) -> ReadonlySingleton<'_, Uint128> {
     singleton_read(storage, KEY_TOTAL_SHARES)
 }
 
-pub fn user_states_read(storage: &dyn Storage) -> ReadonlyBucket<Uint128> {
+pub fn user_states_read(storage: &dyn Storage) -> ReadonlyBucket<'_, Uint128> {
     bucket_read(storage, KEY_USER_STATES)
 }
 
-pub fn user_states_store(storage: &mut dyn Storage) -> Bucket<Uint128> {
+pub fn user_states_store(storage: &mut dyn Storage) -> Bucket<'_, Uint128> {
     bucket(storage, KEY_USER_STATES)
 }
 
-pub fn temp_send_store(storage: &mut dyn Storage) -> Singleton<TempSendData> {
+// the changelogs keep the shares held at the beginning of each height they changed in,
+// heights without an entry hold the value of the next entry, or the current one.
+fn user_shares_changelog_store<'a>(
+    storage: &'a mut dyn Storage,
+    user: &CanonicalAddr,
+) -> Bucket<'a, Uint128> {
+    Bucket::multilevel(storage, &[KEY_USER_SHARES_CHANGELOG, user.as_slice()])
+}
+
+fn user_shares_changelog_read<'a>(
+    storage: &'a dyn Storage,
+    user: &CanonicalAddr,
+) -> ReadonlyBucket<'a, Uint128> {
+    ReadonlyBucket::multilevel(storage, &[KEY_USER_SHARES_CHANGELOG, user.as_slice()])
+}
+
+fn total_shares_changelog_store(storage: &mut dyn Storage) -> Bucket<'_, Uint128> {
+    bucket(storage, KEY_TOTAL_SHARES_CHANGELOG)
+}
+
+fn total_shares_changelog_read(storage: &dyn Storage) -> ReadonlyBucket<'_, Uint128> {
+    bucket_read(storage, KEY_TOTAL_SHARES_CHANGELOG)
+}
+
+/// ShareSeconds is the sum of shares * seconds held up to its time, and the shares held after.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ShareSeconds {
+    pub shares: Uint128,
+    pub cumulative: Uint128,
+}
+
+// share seconds are observed by time whenever the shares change,
+// they accrue from the first change on.
+fn user_share_seconds_store<'a>(
+    storage: &'a mut dyn Storage,
+    user: &CanonicalAddr,
+) -> Bucket<'a, ShareSeconds> {
+    Bucket::multilevel(storage, &[KEY_USER_SHARE_SECONDS, user.as_slice()])
+}
+
+fn user_share_seconds_read<'a>(
+    storage: &'a dyn Storage,
+    user: &CanonicalAddr,
+) -> ReadonlyBucket<'a, ShareSeconds> {
+    ReadonlyBucket::multilevel(storage, &[KEY_USER_SHARE_SECONDS, user.as_slice()])
+}
+
+fn total_share_seconds_store(storage: &mut dyn Storage) -> Bucket<'_, ShareSeconds> {
+    bucket(storage, KEY_TOTAL_SHARE_SECONDS)
+}
+
+fn total_share_seconds_read(storage: &dyn Storage) -> ReadonlyBucket<'_, ShareSeconds> {
+    bucket_read(storage, KEY_TOTAL_SHARE_SECONDS)
+}
+
+// share_seconds_at accrues the last observation at or before time up to time.
+fn share_seconds_at(
+    observations: &ReadonlyBucket<'_, ShareSeconds>,
+    time: u64,
+) -> StdResult<ShareSeconds> {
+    match observations
+        .range(
+            None,
+            Some(&time.saturating_add(1).to_be_bytes()),
+            Order::Descending,
+        )
+        .next()
+    {
+        Some(item) => {
+            let (k, v) = item?;
+            let mut last = [0u8; 8];
+            last.copy_from_slice(&k);
+            let elapsed = Uint128::from(time - u64::from_be_bytes(last));
+            Ok(ShareSeconds {
+                cumulative: v.cumulative.checked_add(v.shares.checked_mul(elapsed)?)?,
+                shares: v.shares,
+            })
+        }
+        None => Ok(ShareSeconds {
+            shares: Uint128::zero(),
+            cumulative: Uint128::zero(),
+        }),
+    }
+}
+
+/// user_share_seconds returns the share seconds of user accrued up to time.
+pub fn user_share_seconds(
+    storage: &dyn Storage,
+    user: &CanonicalAddr,
+    time: u64,
+) -> StdResult<Uint128> {
+    Ok(share_seconds_at(&user_share_seconds_read(storage, user), time)?.cumulative)
+}
+
+/// total_share_seconds returns the share seconds of total_shares accrued up to time.
+pub fn total_share_seconds(storage: &dyn Storage, time: u64) -> StdResult<Uint128> {
+    Ok(share_seconds_at(&total_share_seconds_read(storage), time)?.cumulative)
+}
+
+/// save_user_shares updates user_states at height and time, and keeps the shares
+/// for snapshot and share seconds queries, zero shares remove the entry.
+pub fn save_user_shares(
+    storage: &mut dyn Storage,
+    height: u64,
+    time: u64,
+    user: &CanonicalAddr,
+    shares: Uint128,
+) -> StdResult<()> {
+    let cumulative = share_seconds_at(&user_share_seconds_read(storage, user), time)?.cumulative;
+    user_share_seconds_store(storage, user)
+        .save(&time.to_be_bytes(), &ShareSeconds { shares, cumulative })?;
+    let key = height.to_be_bytes();
+    if user_shares_changelog_read(storage, user)
+        .may_load(&key)?
+        .is_none()
+    {
+        let old = user_states_read(storage)
+            .may_load(user.as_slice())?
+            .unwrap_or_default();
+        user_shares_changelog_store(storage, user).save(&key, &old)?;
+    }
+    if shares.is_zero() {
+        user_states_store(storage).remove(user.as_slice());
+    } else {
+        user_states_store(storage).save(user.as_slice(), &shares)?;
+        holders_store(storage).save(user.as_slice(), &true)?;
+    }
+    Ok(())
+}
+
+/// holders keeps every address that held shares, exited ones included.
+pub fn holders_store(storage: &mut dyn Storage) -> Bucket<'_, bool> {
+    bucket(storage, KEY_HOLDERS)
+}
+
+pub fn holders_read(storage: &dyn Storage) -> ReadonlyBucket<'_, bool> {
+    bucket_read(storage, KEY_HOLDERS)
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MerkleRoot {
+    pub merkle_root: String,
+    pub total_shares: Uint128,
+}
+
+pub fn merkle_roots_store(storage: &mut dyn Storage) -> Bucket<'_, MerkleRoot> {
+    bucket(storage, KEY_MERKLE_ROOTS)
+}
+
+pub fn merkle_roots_read(storage: &dyn Storage) -> ReadonlyBucket<'_, MerkleRoot> {
+    bucket_read(storage, KEY_MERKLE_ROOTS)
+}
+
+/// Airdrop is split pro-rata to the shares at the beginning of `height`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Airdrop {
+    pub height: u64,
+    pub amount: Uint128,
+    pub total_shares: Uint128,
+    pub claimed: Uint128,
+}
+
+/// airdrops of a token are keyed by their height, so a token can be airdropped again.
+pub fn airdrops_store<'a>(
+    storage: &'a mut dyn Storage,
+    token: &CanonicalAddr,
+) -> Bucket<'a, Airdrop> {
+    Bucket::multilevel(storage, &[KEY_AIRDROPS, token.as_slice()])
+}
+
+pub fn airdrops_read<'a>(
+    storage: &'a dyn Storage,
+    token: &CanonicalAddr,
+) -> ReadonlyBucket<'a, Airdrop> {
+    ReadonlyBucket::multilevel(storage, &[KEY_AIRDROPS, token.as_slice()])
+}
+
+/// unclaimed_airdrops sums what is left to claim over every airdrop of the token.
+pub fn unclaimed_airdrops(storage: &dyn Storage, token: &CanonicalAddr) -> StdResult<Uint128> {
+    airdrops_read(storage, token)
+        .range(None, None, Order::Ascending)
+        .try_fold(Uint128::zero(), |acc, item| {
+            let (_, airdrop) = item?;
+            Ok(acc + (airdrop.amount - airdrop.claimed))
+        })
+}
+
+/// airdrop_claims keeps the amount claimed by each user of the airdrop of the token at height.
+pub fn airdrop_claims_store<'a>(
+    storage: &'a mut dyn Storage,
+    token: &CanonicalAddr,
+    height: u64,
+) -> Bucket<'a, Uint128> {
+    Bucket::multilevel(
+        storage,
+        &[KEY_AIRDROP_CLAIMS, token.as_slice(), &height.to_be_bytes()],
+    )
+}
+
+pub fn airdrop_claims_read<'a>(
+    storage: &'a dyn Storage,
+    token: &CanonicalAddr,
+    height: u64,
+) -> ReadonlyBucket<'a, Uint128> {
+    ReadonlyBucket::multilevel(
+        storage,
+        &[KEY_AIRDROP_CLAIMS, token.as_slice(), &height.to_be_bytes()],
+    )
+}
+
+/// compound_incentive is the part of the idle ANC paid to the caller of `Compound`.
+pub fn compound_incentive_store(storage: &mut dyn Storage) -> Singleton<'_, Decimal> {
+    singleton(storage, KEY_COMPOUND_INCENTIVE)
+}
+
+pub fn compound_incentive_read(storage: &dyn Storage) -> ReadonlySingleton<'_, Decimal> {
+    singleton_read(storage, KEY_COMPOUND_INCENTIVE)
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Compound {
+    pub height: u64,
+    pub time: u64,
+    pub staked: Uint128,
+    pub incentive: Uint128,
+    pub exchange_rate_before: Decimal,
+    pub exchange_rate_after: Decimal,
+}
+
+pub fn last_compound_store(storage: &mut dyn Storage) -> Singleton<'_, Compound> {
+    singleton(storage, KEY_LAST_COMPOUND)
+}
+
+pub fn last_compound_read(storage: &dyn Storage) -> ReadonlySingleton<'_, Compound> {
+    singleton_read(storage, KEY_LAST_COMPOUND)
+}
+
+/// save_total_shares updates total_shares at height and time, and keeps it
+/// for snapshot and share seconds queries.
+pub fn save_total_shares(
+    storage: &mut dyn Storage,
+    height: u64,
+    time: u64,
+    total_shares: Uint128,
+) -> StdResult<()> {
+    let cumulative = share_seconds_at(&total_share_seconds_read(storage), time)?.cumulative;
+    total_share_seconds_store(storage).save(
+        &time.to_be_bytes(),
+        &ShareSeconds {
+            shares: total_shares,
+            cumulative,
+        },
+    )?;
+    let key = height.to_be_bytes();
+    if total_shares_changelog_read(storage)
+        .may_load(&key)?
+        .is_none()
+    {
+        let old = total_shares_read(storage).load()?;
+        total_shares_changelog_store(storage).save(&key, &old)?;
+    }
+    total_shares_store(storage).save(&total_shares)
+}
+
+/// user_shares_at_height returns the shares of user at the beginning of height.
+pub fn user_shares_at_height(
+    storage: &dyn Storage,
+    user: &CanonicalAddr,
+    height: u64,
+) -> StdResult<Uint128> {
+    match user_shares_changelog_read(storage, user)
+        .range(Some(&height.to_be_bytes()), None, Order::Ascending)
+        .next()
+    {
+        Some(item) => Ok(item?.1),
+        None => Ok(user_states_read(storage)
+            .may_load(user.as_slice())?
+            .unwrap_or_default()),
+    }
+}
+
+/// total_shares_at_height returns total_shares at the beginning of height.
+pub fn total_shares_at_height(storage: &dyn Storage, height: u64) -> StdResult<Uint128> {
+    match total_shares_changelog_read(storage)
+        .range(Some(&height.to_be_bytes()), None, Order::Ascending)
+        .next()
+    {
+        Some(item) => Ok(item?.1),
+        None => total_shares_read(storage).load(),
+    }
+}
+
+pub fn temp_send_store(storage: &mut dyn Storage) -> Singleton<'_, TempSendData> {
     singleton(storage, KEY_TEMP_SEND)
 }
 
-pub fn temp_send_read(storage: &dyn Storage) -> ReadonlySingleton<TempSendData> {
+pub fn temp_send_read(storage: &dyn Storage) -> ReadonlySingleton<'_, TempSendData> {
     singleton_read(storage, KEY_TEMP_SEND)
 }
+
+pub fn paused_store(storage: &mut dyn Storage) -> Singleton<'_, bool> {
+    singleton(storage, KEY_PAUSED)
+}
+
+pub fn paused_read(storage: &dyn Storage) -> ReadonlySingleton<'_, bool> {
+    singleton_read(storage, KEY_PAUSED)
+}
+
+pub fn deposit_limits_store(storage: &mut dyn Storage) -> Singleton<'_, DepositLimits> {
+    singleton(storage, KEY_DEPOSIT_LIMITS)
+}
+
+pub fn deposit_limits_read(storage: &dyn Storage) -> ReadonlySingleton<'_, DepositLimits> {
+    singleton_read(storage, KEY_DEPOSIT_LIMITS)
+}
+
+pub fn allowlist_enabled_store(storage: &mut dyn Storage) -> Singleton<'_, bool> {
+    singleton(storage, KEY_ALLOWLIST_ENABLED)
+}
+
+pub fn allowlist_enabled_read(storage: &dyn Storage) -> ReadonlySingleton<'_, bool> {
+    singleton_read(storage, KEY_ALLOWLIST_ENABLED)
+}
+
+pub fn allowlist_store(storage: &mut dyn Storage) -> Bucket<'_, bool> {
+    bucket(storage, KEY_ALLOWLIST)
+}
+
+pub fn allowlist_read(storage: &dyn Storage) -> ReadonlyBucket<'_, bool> {
+    bucket_read(storage, KEY_ALLOWLIST)
+}
+
+pub fn fee_exemptions_store(storage: &mut dyn Storage) -> Bucket<'_, bool> {
+    bucket(storage, KEY_FEE_EXEMPTIONS)
+}
+
+pub fn fee_exemptions_read(storage: &dyn Storage) -> ReadonlyBucket<'_, bool> {
+    bucket_read(storage, KEY_FEE_EXEMPTIONS)
+}
+
+pub fn exit_fee_store(storage: &mut dyn Storage) -> Singleton<'_, ExitFee> {
+    singleton(storage, KEY_EXIT_FEE)
+}
+
+pub fn exit_fee_read(storage: &dyn Storage) -> ReadonlySingleton<'_, ExitFee> {
+    singleton_read(storage, KEY_EXIT_FEE)
+}
+
+/// last_deposit keeps the block time in seconds of each user's latest deposit.
+pub fn last_deposit_store(storage: &mut dyn Storage) -> Bucket<'_, u64> {
+    bucket(storage, KEY_LAST_DEPOSIT)
+}
+
+pub fn last_deposit_read(storage: &dyn Storage) -> ReadonlyBucket<'_, u64> {
+    bucket_read(storage, KEY_LAST_DEPOSIT)
+}
+
+pub fn lock_config_store(storage: &mut dyn Storage) -> Singleton<'_, LockConfig> {
+    singleton(storage, KEY_LOCK_CONFIG)
+}
+
+pub fn lock_config_read(storage: &dyn Storage) -> ReadonlySingleton<'_, LockConfig> {
+    singleton_read(storage, KEY_LOCK_CONFIG)
+}
+
+pub fn position_count_store(storage: &mut dyn Storage) -> Singleton<'_, u64> {
+    singleton(storage, KEY_POSITION_COUNT)
+}
+
+pub fn position_count_read(storage: &dyn Storage) -> ReadonlySingleton<'_, u64> {
+    singleton_read(storage, KEY_POSITION_COUNT)
+}
+
+/// positions are stored per user, keyed by position id.
+pub fn positions_store<'a>(
+    storage: &'a mut dyn Storage,
+    user: &CanonicalAddr,
+) -> Bucket<'a, Position> {
+    Bucket::multilevel(storage, &[KEY_POSITIONS, user.as_slice()])
+}
+
+pub fn positions_read<'a>(
+    storage: &'a dyn Storage,
+    user: &CanonicalAddr,
+) -> ReadonlyBucket<'a, Position> {
+    ReadonlyBucket::multilevel(storage, &[KEY_POSITIONS, user.as_slice()])
+}
+
+pub fn read_positions(
+    storage: &dyn Storage,
+    user: &CanonicalAddr,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<Position>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|x| (x + 1).to_be_bytes().to_vec());
+
+    positions_read(storage, user)
+        .range(start.as_deref(), None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (_, v) = item?;
+            Ok(v)
+        })
+        .collect()
+}
+
+pub fn fee_mode_store(storage: &mut dyn Storage) -> Singleton<'_, FeeMode> {
+    singleton(storage, KEY_FEE_MODE)
+}
+
+pub fn fee_mode_read(storage: &dyn Storage) -> ReadonlySingleton<'_, FeeMode> {
+    singleton_read(storage, KEY_FEE_MODE)
+}
+
+/// claimable_fees is the ANC held by the contract for dev, it is never staked into gov.
+pub fn claimable_fees_store(storage: &mut dyn Storage) -> Singleton<'_, Uint128> {
+    singleton(storage, KEY_CLAIMABLE_FEES)
+}
+
+pub fn claimable_fees_read(storage: &dyn Storage) -> ReadonlySingleton<'_, Uint128> {
+    singleton_read(storage, KEY_CLAIMABLE_FEES)
+}
+
+pub fn stray_anc_policy_store(storage: &mut dyn Storage) -> Singleton<'_, StrayAncPolicy> {
+    singleton(storage, KEY_STRAY_ANC_POLICY)
+}
+
+pub fn stray_anc_policy_read(storage: &dyn Storage) -> ReadonlySingleton<'_, StrayAncPolicy> {
+    singleton_read(storage, KEY_STRAY_ANC_POLICY)
+}
+
+/// dev_vesting is the vesting duration of dev fee shares in seconds, zero vests instantly.
+pub fn dev_vesting_store(storage: &mut dyn Storage) -> Singleton<'_, u64> {
+    singleton(storage, KEY_DEV_VESTING)
+}
+
+pub fn dev_vesting_read(storage: &dyn Storage) -> ReadonlySingleton<'_, u64> {
+    singleton_read(storage, KEY_DEV_VESTING)
+}
+
+/// vesting schedules are stored per address, keyed by start time.
+pub fn vesting_store<'a>(
+    storage: &'a mut dyn Storage,
+    address: &CanonicalAddr,
+) -> Bucket<'a, VestingSchedule> {
+    Bucket::multilevel(storage, &[KEY_VESTING, address.as_slice()])
+}
+
+pub fn vesting_read<'a>(
+    storage: &'a dyn Storage,
+    address: &CanonicalAddr,
+) -> ReadonlyBucket<'a, VestingSchedule> {
+    ReadonlyBucket::multilevel(storage, &[KEY_VESTING, address.as_slice()])
+}
+
+pub fn meta_gov_config_store(storage: &mut dyn Storage) -> Singleton<'_, MetaGovConfig> {
+    singleton(storage, KEY_META_GOV_CONFIG)
+}
+
+pub fn meta_gov_config_read(storage: &dyn Storage) -> ReadonlySingleton<'_, MetaGovConfig> {
+    singleton_read(storage, KEY_META_GOV_CONFIG)
+}
+
+pub fn proposal_count_store(storage: &mut dyn Storage) -> Singleton<'_, u64> {
+    singleton(storage, KEY_PROPOSAL_COUNT)
+}
+
+pub fn proposal_count_read(storage: &dyn Storage) -> ReadonlySingleton<'_, u64> {
+    singleton_read(storage, KEY_PROPOSAL_COUNT)
+}
+
+pub fn proposals_store(storage: &mut dyn Storage) -> Bucket<'_, Proposal> {
+    bucket(storage, KEY_PROPOSALS)
+}
+
+pub fn proposals_read(storage: &dyn Storage) -> ReadonlyBucket<'_, Proposal> {
+    bucket_read(storage, KEY_PROPOSALS)
+}
+
+/// votes are stored per proposal, keyed by voter.
+pub fn votes_store(storage: &mut dyn Storage, proposal_id: u64) -> Bucket<'_, Vote> {
+    Bucket::multilevel(storage, &[KEY_VOTES, &proposal_id.to_be_bytes()])
+}
+
+pub fn votes_read(storage: &dyn Storage, proposal_id: u64) -> ReadonlyBucket<'_, Vote> {
+    ReadonlyBucket::multilevel(storage, &[KEY_VOTES, &proposal_id.to_be_bytes()])
+}
+
+/// vote locks are stored per voter, keyed by proposal id.
+pub fn vote_locks_store<'a>(
+    storage: &'a mut dyn Storage,
+    voter: &CanonicalAddr,
+) -> Bucket<'a, VoteLock> {
+    Bucket::multilevel(storage, &[KEY_VOTE_LOCKS, voter.as_slice()])
+}
+
+pub fn vote_locks_read<'a>(
+    storage: &'a dyn Storage,
+    voter: &CanonicalAddr,
+) -> ReadonlyBucket<'a, VoteLock> {
+    ReadonlyBucket::multilevel(storage, &[KEY_VOTE_LOCKS, voter.as_slice()])
+}
+
+pub fn read_proposals(
+    storage: &dyn Storage,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<Proposal>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|x| (x + 1).to_be_bytes().to_vec());
+
+    proposals_read(storage)
+        .range(start.as_deref(), None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (_, v) = item?;
+            Ok(v)
+        })
+        .collect()
+}
+
+pub fn temp_poll_store(storage: &mut dyn Storage) -> Singleton<'_, TempPollData> {
+    singleton(storage, KEY_TEMP_POLL)
+}
+
+pub fn temp_poll_read(storage: &dyn Storage) -> ReadonlySingleton<'_, TempPollData> {
+    singleton_read(storage, KEY_TEMP_POLL)
+}
+
+pub fn gov_polls_store(storage: &mut dyn Storage) -> Bucket<'_, GovPoll> {
+    bucket(storage, KEY_GOV_POLLS)
+}
+
+pub fn gov_polls_read(storage: &dyn Storage) -> ReadonlyBucket<'_, GovPoll> {
+    bucket_read(storage, KEY_GOV_POLLS)
+}
+
+pub fn outstanding_poll_deposits_store(storage: &mut dyn Storage) -> Singleton<'_, Uint128> {
+    singleton(storage, KEY_OUTSTANDING_POLL_DEPOSITS)
+}
+
+pub fn outstanding_poll_deposits_read(storage: &dyn Storage) -> ReadonlySingleton<'_, Uint128> {
+    singleton_read(storage, KEY_OUTSTANDING_POLL_DEPOSITS)
+}
+
+/// pool_poll_deposits is the part of the outstanding poll deposits paid by the pool,
+/// it still belongs to the shares while out of gov.
+pub fn pool_poll_deposits_store(storage: &mut dyn Storage) -> Singleton<'_, Uint128> {
+    singleton(storage, KEY_POOL_POLL_DEPOSITS)
+}
+
+pub fn pool_poll_deposits_read(storage: &dyn Storage) -> ReadonlySingleton<'_, Uint128> {
+    singleton_read(storage, KEY_POOL_POLL_DEPOSITS)
+}
+
+pub fn read_gov_polls(
+    storage: &dyn Storage,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<GovPoll>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.map(|x| (x + 1).to_be_bytes().to_vec());
+
+    gov_polls_read(storage)
+        .range(start.as_deref(), None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (_, v) = item?;
+            Ok(v)
+        })
+        .collect()
+}
+
+pub fn gov_checkpoint_store(storage: &mut dyn Storage) -> Singleton<'_, GovCheckpoint> {
+    singleton(storage, KEY_GOV_CHECKPOINT)
+}
+
+pub fn gov_checkpoint_read(storage: &dyn Storage) -> ReadonlySingleton<'_, GovCheckpoint> {
+    singleton_read(storage, KEY_GOV_CHECKPOINT)
+}
+
+pub fn hooks_store(storage: &mut dyn Storage) -> Singleton<'_, Vec<Hook>> {
+    singleton(storage, KEY_HOOKS)
+}
+
+pub fn hooks_read(storage: &dyn Storage) -> ReadonlySingleton<'_, Vec<Hook>> {
+    singleton_read(storage, KEY_HOOKS)
+}
+
+pub fn read_user_states(
+    storage: &dyn Storage,
+    start_after: Option<CanonicalAddr>,
+    limit: Option<u32>,
+) -> StdResult<Vec<(CanonicalAddr, Uint128)>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = calc_range_start(start_after);
+
+    user_states_read(storage)
+        .range(start.as_deref(), None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (k, v) = item?;
+            Ok((CanonicalAddr::from(k), v))
+        })
+        .collect()
+}
+
+/// read_addresses pages through an address set bucket, ordered by canonical address.
+pub fn read_addresses(
+    bucket: ReadonlyBucket<'_, bool>,
+    start_after: Option<CanonicalAddr>,
+    limit: Option<u32>,
+) -> StdResult<Vec<CanonicalAddr>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = calc_range_start(start_after);
+
+    bucket
+        .range(start.as_deref(), None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (k, _) = item?;
+            Ok(CanonicalAddr::from(k))
+        })
+        .collect()
+}
+
+// this will set the first key after the provided key, by appending a 1 byte
+fn calc_range_start(start_after: Option<CanonicalAddr>) -> Option<Vec<u8>> {
+    start_after.map(|addr| {
+        let mut v = addr.as_slice().to_vec();
+        v.push(1);
+        v
+    })
+}
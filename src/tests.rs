@@ -1,24 +1,39 @@
 use crate::contract::{execute, instantiate, query, reply};
 use crate::error::ContractError;
 use crate::msg::{
-    ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, QueryMsg, StateResponse,
-    UserStateResponse, WITHDRAW_REPLY_ID,
+    AirdropClaimResponse, AirdropResponse, AllowlistResponse, AverageSharesResponse,
+    CompoundResponse, ConfigResponse, Cw20HookMsg, DepositHeadroomResponse, ExecuteMsg,
+    FeeExemptionsResponse, FeeMode, FeeTier, GovKind, GovPollResponse, GovPollsResponse,
+    HealthResponse, HolderShares, HookInfo, HookMsg, HooksResponse, InstantiateMsg,
+    InvariantsResponse, MerkleRootResponse, MetaGovConfigResponse, PositionsResponse,
+    ProposalAction, ProposalResponse, ProposalStatus, ProposalVote, ProposalsResponse, QueryMsg,
+    ShareDelta, SharesAtHeightResponse, StateResponse, StrayAncPolicy, TokenInfo,
+    TotalSharesAtHeightResponse, UserStateAtHeightResponse, UserStateResponse, VestingResponse,
+    CREATE_POLL_REPLY_ID, CREATE_POLL_WITHDRAW_REPLY_ID, HOOK_REPLY_ID, WITHDRAW_REPLY_ID,
 };
 use crate::state::{
-    config_read, feerate_read, temp_send_store, total_shares_read, total_shares_store,
-    user_states_read, user_states_store, Config, TempSendData,
+    config_read, feerate_read, paused_read, positions_store, save_total_shares, save_user_shares,
+    temp_send_store, total_shares_read, total_shares_store, user_states_read, user_states_store,
+    Config, Position, TempSendData,
 };
 
-use crate::mock_querier::mock_dependencies;
+use crate::gov::mirror;
+use crate::merkle::{self, MerkleTree};
+use crate::mock_querier::{
+    mock_dependencies, MirrorStakerResponse, MirrorVoteOption, MirrorVoterInfo,
+};
 use anchor_token::gov::Cw20HookMsg as GovCw20HookMsg;
 use cosmwasm_std::testing::{mock_env, mock_info, MOCK_CONTRACT_ADDR};
 use cosmwasm_std::{
-    from_binary, to_binary, Api, ContractResult, CosmosMsg, Decimal, DepsMut, Reply, Response,
-    StdError, SubMsg, SubMsgExecutionResponse, Uint128, WasmMsg,
+    attr, from_binary, to_binary, Api, Attribute, BankMsg, Binary, Coin, ContractResult, CosmosMsg,
+    Decimal, DepsMut, Event, Reply, Response, StdError, SubMsg, SubMsgExecutionResponse, Timestamp,
+    Uint128, WasmMsg,
 };
-use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg, Expiration};
 
-use anchor_token::gov::{ExecuteMsg as GovExcuteMsg, StakerResponse, VoteOption, VoterInfo};
+use anchor_token::gov::{
+    ExecuteMsg as GovExcuteMsg, PollExecuteMsg, PollStatus, StakerResponse, VoteOption, VoterInfo,
+};
 const DEFAULT_FEERATE: u64 = 2;
 const TEST_NEW_FEERATE: u64 = 5;
 const TEST_DEV: &str = "dev";
@@ -28,6 +43,10 @@ const TEST_ANCHOR_GOV: &str = "anchor_gov";
 const TEST_CREATOR: &str = "creator";
 const TEST_ALICE: &str = "alice";
 const TEST_BOB: &str = "bob";
+const TEST_GUARDIAN: &str = "guardian";
+const TEST_HOOK: &str = "hook";
+const TEST_HOOK2: &str = "hook2";
+const TEST_AIRDROP_TOKEN: &str = "airdrop_token";
 
 //
 fn mock_instantiate(deps: DepsMut) {
@@ -36,6 +55,8 @@ fn mock_instantiate(deps: DepsMut) {
         dev: TEST_DEV.to_string(),
         anchor_token: TEST_ANCHOR_TOKEN.to_string(),
         anchor_gov: TEST_ANCHOR_GOV.to_string(),
+        gov_kind: None,
+        owner: None,
     };
 
     let info = mock_info(TEST_CREATOR, &[]);
@@ -45,7 +66,8 @@ fn mock_instantiate(deps: DepsMut) {
 
 fn set_pedding_owner(deps: DepsMut, new_owner: String) {
     let msg = ExecuteMsg::TransferOwnerShip {
-        new_owner: new_owner,
+        new_owner,
+        expires: Some(Expiration::AtHeight(mock_env().block.height + 100)),
     };
 
     let info = mock_info(TEST_CREATOR, &[]);
@@ -54,6 +76,12 @@ fn set_pedding_owner(deps: DepsMut, new_owner: String) {
         .expect("contract successfully handles RegisterContracts");
 }
 
+fn set_guardian(deps: DepsMut, new_guardian: String) {
+    let msg = ExecuteMsg::TransferGuardian { new_guardian };
+    let info = mock_info(TEST_CREATOR, &[]);
+    execute(deps, mock_env(), info, msg).expect("contract successfully handles TransferGuardian");
+}
+
 #[test]
 fn proper_initialization() {
     let mut deps = mock_dependencies(&[]);
@@ -63,6 +91,8 @@ fn proper_initialization() {
         dev: TEST_DEV.to_string(),
         anchor_token: TEST_ANCHOR_TOKEN.to_string(),
         anchor_gov: TEST_ANCHOR_GOV.to_string(),
+        gov_kind: None,
+        owner: None,
     };
 
     let info = mock_info(TEST_CREATOR, &[]);
@@ -77,8 +107,12 @@ fn proper_initialization() {
             anchor_token: deps.api.addr_canonicalize(TEST_ANCHOR_TOKEN).unwrap(),
             anchor_gov: deps.api.addr_canonicalize(TEST_ANCHOR_GOV).unwrap(),
             dev: deps.api.addr_canonicalize(TEST_DEV).unwrap(),
-            owner: deps.api.addr_canonicalize(TEST_CREATOR).unwrap(),
+            owner: Some(deps.api.addr_canonicalize(TEST_CREATOR).unwrap()),
             pendding_owner: None,
+            pendding_owner_expires: None,
+            guardian: None,
+            pendding_guardian: None,
+            gov_kind: GovKind::Anchor,
         }
     );
     //2. checkout fee rate setting
@@ -148,6 +182,7 @@ fn fails_transfer_ownership_with_unauthorized() {
     mock_instantiate(deps.as_mut());
     let msg = ExecuteMsg::TransferOwnerShip {
         new_owner: TEST_DEV2.to_string(),
+        expires: Some(Expiration::AtHeight(mock_env().block.height + 100)),
     };
 
     let info = mock_info(TEST_DEV, &[]);
@@ -165,6 +200,7 @@ fn fails_transfer_ownership_without_validated_address() {
     mock_instantiate(deps.as_mut());
     let msg = ExecuteMsg::TransferOwnerShip {
         new_owner: "12".to_string(),
+        expires: Some(Expiration::AtHeight(mock_env().block.height + 100)),
     };
 
     let info = mock_info(TEST_CREATOR, &[]);
@@ -184,6 +220,7 @@ fn proper_transfer_ownership() {
     mock_instantiate(deps.as_mut());
     let msg = ExecuteMsg::TransferOwnerShip {
         new_owner: TEST_DEV2.to_string(),
+        expires: Some(Expiration::AtHeight(mock_env().block.height + 100)),
     };
 
     let info = mock_info(TEST_CREATOR, &[]);
@@ -208,6 +245,7 @@ fn proper_transfer_ownership_with_none() {
     mock_instantiate(deps.as_mut());
     let msg = ExecuteMsg::TransferOwnerShip {
         new_owner: TEST_DEV2.to_string(),
+        expires: Some(Expiration::AtHeight(mock_env().block.height + 100)),
     };
 
     let info = mock_info(TEST_CREATOR, &[]);
@@ -227,6 +265,7 @@ fn proper_transfer_ownership_with_none() {
     // reset
     let msg = ExecuteMsg::TransferOwnerShip {
         new_owner: String::default(),
+        expires: None,
     };
 
     let info = mock_info(TEST_CREATOR, &[]);
@@ -281,13 +320,341 @@ fn proper_accepted_owner() {
     assert_eq!(
         config,
         Config {
-            owner: deps.api.addr_canonicalize(TEST_DEV2).unwrap(),
+            owner: Some(deps.api.addr_canonicalize(TEST_DEV2).unwrap()),
             pendding_owner: None,
+            pendding_owner_expires: None,
+            ..config.clone()
+        }
+    );
+}
+
+#[test]
+fn fails_transfer_ownership_with_expired_deadline() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    let env = mock_env();
+    let msg = ExecuteMsg::TransferOwnerShip {
+        new_owner: TEST_DEV2.to_string(),
+        expires: Some(Expiration::AtHeight(env.block.height)),
+    };
+    let info = mock_info(TEST_CREATOR, &[]);
+    let res = execute(deps.as_mut(), env, info, msg);
+    match res {
+        Err(ContractError::InvalidExpiration {}) => (),
+        _ => panic!("Must return error"),
+    }
+}
+
+#[test]
+fn fails_accepted_owner_after_expiration() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    let mut env = mock_env();
+    let expires = Expiration::AtHeight(env.block.height + 100);
+    let msg = ExecuteMsg::TransferOwnerShip {
+        new_owner: TEST_DEV2.to_string(),
+        expires: Some(expires),
+    };
+    let info = mock_info(TEST_CREATOR, &[]);
+    execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    // deadline is exposed by query_config
+    let res = query(deps.as_ref(), env.clone(), QueryMsg::Config {}).unwrap();
+    let config_response: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(config_response.pendding_owner_expires, Some(expires));
+
+    env.block.height += 100;
+    let info = mock_info(TEST_DEV2, &[]);
+    let res = execute(deps.as_mut(), env, info, ExecuteMsg::AcceptOwner {});
+    match res {
+        Err(ContractError::OwnershipTransferExpired {}) => (),
+        _ => panic!("Must return error"),
+    }
+}
+
+#[test]
+fn fails_transfer_ownership_beyond_window() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    let env = mock_env();
+    for expires in [
+        None,
+        Some(Expiration::Never {}),
+        Some(Expiration::AtHeight(env.block.height + 432_001)),
+        Some(Expiration::AtTime(
+            env.block.time.plus_seconds(30 * 86400 + 1),
+        )),
+    ]
+    .iter()
+    {
+        let msg = ExecuteMsg::TransferOwnerShip {
+            new_owner: TEST_DEV2.to_string(),
+            expires: *expires,
+        };
+        let info = mock_info(TEST_CREATOR, &[]);
+        match execute(deps.as_mut(), env.clone(), info, msg) {
+            Err(ContractError::InvalidExpiration {}) if expires.is_none() => (),
+            Err(ContractError::ExpirationTooLong {}) if expires.is_some() => (),
+            _ => panic!("Must return error"),
+        }
+    }
+}
+
+#[test]
+fn proper_renounce_ownership() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    mock_meta_gov(deps.as_mut(), 0);
+    set_pedding_owner(deps.as_mut(), TEST_DEV2.to_string());
+
+    let info = mock_info(TEST_DEV, &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::RenounceOwnership {},
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("Must return error"),
+    }
+
+    let info = mock_info(TEST_CREATOR, &[]);
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::RenounceOwnership {},
+    )
+    .unwrap();
+
+    let config: Config = config_read(deps.as_ref().storage).load().unwrap();
+    assert_eq!(config.owner, None);
+    assert_eq!(config.pendding_owner, None);
+    // proposals can not change the config either
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::MetaGovConfig {}).unwrap();
+    let meta_gov_config_response: MetaGovConfigResponse = from_binary(&res).unwrap();
+    assert!(!meta_gov_config_response.enabled);
+
+    // config is immutable now
+    let info = mock_info(TEST_CREATOR, &[]);
+    let msg = ExecuteMsg::UpdateFeeRate {
+        new_feerate: Decimal::percent(TEST_NEW_FEERATE),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("Must return error"),
+    }
+    let info = mock_info(TEST_DEV2, &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::AcceptOwner {});
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("Must return error"),
+    }
+}
+
+#[test]
+fn fails_transfer_guardian_with_unauthorized() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    let msg = ExecuteMsg::TransferGuardian {
+        new_guardian: TEST_GUARDIAN.to_string(),
+    };
+    let info = mock_info(TEST_DEV, &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("Must return error"),
+    }
+
+    // pendding guardian only, wrong sender.
+    set_guardian(deps.as_mut(), TEST_GUARDIAN.to_string());
+    let info = mock_info(TEST_DEV, &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::AcceptGuardian {},
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("Must return error"),
+    }
+}
+
+#[test]
+fn proper_accepted_guardian() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    set_guardian(deps.as_mut(), TEST_GUARDIAN.to_string());
+
+    let config: Config = config_read(deps.as_ref().storage).load().unwrap();
+    assert_eq!(
+        config.pendding_guardian,
+        Some(deps.api.addr_canonicalize(TEST_GUARDIAN).unwrap())
+    );
+
+    let info = mock_info(TEST_GUARDIAN, &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::AcceptGuardian {},
+    )
+    .unwrap();
+    assert_eq!(0, res.messages.len());
+
+    let config: Config = config_read(deps.as_ref().storage).load().unwrap();
+    assert_eq!(
+        config,
+        Config {
+            guardian: Some(deps.api.addr_canonicalize(TEST_GUARDIAN).unwrap()),
+            pendding_guardian: None,
             ..config.clone()
         }
     );
 }
 
+#[test]
+fn proper_remove_guardian() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    set_guardian(deps.as_mut(), TEST_GUARDIAN.to_string());
+    let info = mock_info(TEST_GUARDIAN, &[]);
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::AcceptGuardian {},
+    )
+    .unwrap();
+    set_guardian(deps.as_mut(), TEST_ALICE.to_string());
+
+    let info = mock_info(TEST_GUARDIAN, &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::RemoveGuardian {},
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("Must return error"),
+    }
+
+    let info = mock_info(TEST_CREATOR, &[]);
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::RemoveGuardian {},
+    )
+    .unwrap();
+    let config: Config = config_read(deps.as_ref().storage).load().unwrap();
+    assert_eq!(config.guardian, None);
+    assert_eq!(config.pendding_guardian, None);
+
+    // the removed guardian can not pause anymore.
+    let info = mock_info(TEST_GUARDIAN, &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Pause {});
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("Must return error"),
+    }
+}
+
+#[test]
+fn fails_pause_with_unauthorized() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    set_guardian(deps.as_mut(), TEST_GUARDIAN.to_string());
+
+    // pendding guardian is not guardian yet.
+    let info = mock_info(TEST_GUARDIAN, &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Pause {});
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("Must return error"),
+    }
+
+    // guardian can not unpause.
+    let info = mock_info(TEST_GUARDIAN, &[]);
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::AcceptGuardian {},
+    )
+    .unwrap();
+    let info = mock_info(TEST_GUARDIAN, &[]);
+    execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Pause {}).unwrap();
+    let info = mock_info(TEST_GUARDIAN, &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Unpause {});
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("Must return error"),
+    }
+
+    // guardian can not touch fees.
+    let info = mock_info(TEST_GUARDIAN, &[]);
+    let msg = ExecuteMsg::UpdateFeeRate {
+        new_feerate: Decimal::percent(TEST_NEW_FEERATE),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("Must return error"),
+    }
+}
+
+#[test]
+fn proper_pause() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    set_guardian(deps.as_mut(), TEST_GUARDIAN.to_string());
+    let info = mock_info(TEST_GUARDIAN, &[]);
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::AcceptGuardian {},
+    )
+    .unwrap();
+
+    let info = mock_info(TEST_GUARDIAN, &[]);
+    execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Pause {}).unwrap();
+    assert!(paused_read(deps.as_ref().storage).load().unwrap());
+
+    // deposit and withdraw are rejected while paused
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_ALICE.to_string(),
+        amount: Uint128::from(11u128),
+        msg: to_binary(&Cw20HookMsg::StakingTokens {
+            lock_duration: None,
+        })
+        .unwrap(),
+    });
+    let info = mock_info(TEST_ANCHOR_TOKEN, &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::Paused {}) => (),
+        _ => panic!("Must return error"),
+    }
+    let msg = ExecuteMsg::WithdrawToken { amount: None };
+    let info = mock_info(TEST_ALICE, &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::Paused {}) => (),
+        _ => panic!("Must return error"),
+    }
+
+    // owner resumes
+    let info = mock_info(TEST_CREATOR, &[]);
+    execute(deps.as_mut(), mock_env(), info, ExecuteMsg::Unpause {}).unwrap();
+    assert!(!paused_read(deps.as_ref().storage).load().unwrap());
+}
+
 #[test]
 fn fails_update_feerate_with_unauthorized() {
     let mut deps = mock_dependencies(&[]);
@@ -333,58 +700,94 @@ fn proper_update_feerate() {
 }
 
 #[test]
-fn fails_receive_with_unauthorized() {
+fn fails_update_fee_tiers_out_of_limits() {
     let mut deps = mock_dependencies(&[]);
     mock_instantiate(deps.as_mut());
-    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
-        sender: TEST_ALICE.to_string(),
-        amount: Uint128::from(11u128),
-        msg: to_binary(&Cw20HookMsg::StakingTokens {}).unwrap(),
-    });
-    let info = mock_info(&(TEST_ANCHOR_TOKEN.to_string() + "2"), &[]);
+
+    // rates must not increase with the amount
+    let msg = ExecuteMsg::UpdateFeeTiers {
+        tiers: vec![
+            FeeTier {
+                min_amount: Uint128::from(1000u128),
+                feerate: Decimal::percent(1),
+            },
+            FeeTier {
+                min_amount: Uint128::from(10000u128),
+                feerate: Decimal::percent(DEFAULT_FEERATE),
+            },
+        ],
+    };
+    let info = mock_info(TEST_CREATOR, &[]);
     let res = execute(deps.as_mut(), mock_env(), info, msg);
     match res {
-        Err(ContractError::Unauthorized {}) => (),
+        Err(ContractError::InvalidFeeTiers {}) => (),
         _ => panic!("Must return error"),
     }
-}
 
-#[test]
-fn fails_receive_with_zero_amount() {
-    let mut deps = mock_dependencies(&[]);
-    mock_instantiate(deps.as_mut());
-    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
-        sender: TEST_ALICE.to_string(),
-        amount: Uint128::zero(),
-        msg: to_binary(&Cw20HookMsg::StakingTokens {}).unwrap(),
-    });
-    let info = mock_info(&(TEST_ANCHOR_TOKEN.to_string()), &[]);
+    // amounts must increase
+    let msg = ExecuteMsg::UpdateFeeTiers {
+        tiers: vec![
+            FeeTier {
+                min_amount: Uint128::from(1000u128),
+                feerate: Decimal::percent(1),
+            },
+            FeeTier {
+                min_amount: Uint128::from(1000u128),
+                feerate: Decimal::zero(),
+            },
+        ],
+    };
+    let info = mock_info(TEST_CREATOR, &[]);
     let res = execute(deps.as_mut(), mock_env(), info, msg);
     match res {
-        Err(ContractError::InsufficientFunds {}) => (),
+        Err(ContractError::InvalidFeeTiers {}) => (),
+        _ => panic!("Must return error"),
+    }
+
+    // base feerate can not go below the tiers
+    let msg = ExecuteMsg::UpdateFeeTiers {
+        tiers: vec![FeeTier {
+            min_amount: Uint128::from(1000u128),
+            feerate: Decimal::percent(1),
+        }],
+    };
+    let info = mock_info(TEST_CREATOR, &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let msg = ExecuteMsg::UpdateFeeRate {
+        new_feerate: Decimal::zero(),
+    };
+    let info = mock_info(TEST_CREATOR, &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::InvalidFeeTiers {}) => (),
         _ => panic!("Must return error"),
     }
 }
 
 #[test]
-pub fn proper_receive_with_dev_fee() {
+fn proper_receive_with_fee_tiers() {
     let mut deps = mock_dependencies(&[]);
     mock_instantiate(deps.as_mut());
-    let transfer_amount_alice = Uint128::from(1_000_000u128);
-    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
-        sender: TEST_ALICE.to_string(),
-        amount: transfer_amount_alice,
-        msg: to_binary(&Cw20HookMsg::StakingTokens {}).unwrap(),
-    });
-    let info = mock_info(&(TEST_ANCHOR_TOKEN.to_string()), &[]);
+    let tiers = vec![
+        FeeTier {
+            min_amount: Uint128::from(1_000u128),
+            feerate: Decimal::percent(1),
+        },
+        FeeTier {
+            min_amount: Uint128::from(1_000_000u128),
+            feerate: Decimal::zero(),
+        },
+    ];
+    let msg = ExecuteMsg::UpdateFeeTiers {
+        tiers: tiers.clone(),
+    };
+    let info = mock_info(TEST_CREATOR, &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-    // deposit MOCK_CONTRACT_ADDR some tokens.
-    let transfer_contract_amount1 = Uint128::from(2_000_000u128);
     deps.querier.with_token_balances(&[(
         &TEST_ANCHOR_TOKEN.to_string(),
-        &[(&MOCK_CONTRACT_ADDR.to_string(), &transfer_contract_amount1)],
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(10_000u128))],
     )]);
-
     deps.querier.with_gov_stakers(&[(
         &TEST_ANCHOR_GOV.to_string(),
         &[(
@@ -397,28 +800,125 @@ pub fn proper_receive_with_dev_fee() {
         )],
     )]);
 
-    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
-    let msg = res.messages.get(0).expect("no message");
-    // assert for Send to TEST_ANCHOR_GOV SubMsg
-    assert_eq!(
-        msg,
-        &SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: TEST_ANCHOR_TOKEN.to_string(),
-            msg: to_binary(&Cw20ExecuteMsg::Send {
-                contract: TEST_ANCHOR_GOV.to_string(),
-                amount: transfer_contract_amount1,
-                msg: to_binary(&GovCw20HookMsg::StakeVotingTokens {}).unwrap(),
-            })
-            .unwrap(),
-            funds: vec![],
-        }))
-    );
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::State {}).unwrap();
+    let state_response: StateResponse = from_binary(&res).unwrap();
+    assert_eq!(state_response.fee_tiers, tiers);
 
-    //dev check
-    let key = deps.api.addr_canonicalize(TEST_DEV).unwrap();
-    let dev_shares = user_states_read(deps.as_ref().storage)
-        .may_load(&key.as_slice())
-        .unwrap_or_default()
+    // 10_000 reaches the first tier only
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_ALICE.to_string(),
+        amount: Uint128::from(10_000u128),
+        msg: to_binary(&Cw20HookMsg::StakingTokens {
+            lock_duration: None,
+        })
+        .unwrap(),
+    });
+    let info = mock_info(TEST_ANCHOR_TOKEN, &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let key = deps.api.addr_canonicalize(TEST_DEV).unwrap();
+    let dev_shares = user_states_read(deps.as_ref().storage)
+        .load(key.as_slice())
+        .unwrap();
+    assert_eq!(dev_shares, Uint128::from(100u128));
+}
+
+#[test]
+fn fails_receive_with_unauthorized() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_ALICE.to_string(),
+        amount: Uint128::from(11u128),
+        msg: to_binary(&Cw20HookMsg::StakingTokens {
+            lock_duration: None,
+        })
+        .unwrap(),
+    });
+    let info = mock_info(&(TEST_ANCHOR_TOKEN.to_string() + "2"), &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("Must return error"),
+    }
+}
+
+#[test]
+fn fails_receive_with_zero_amount() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_ALICE.to_string(),
+        amount: Uint128::zero(),
+        msg: to_binary(&Cw20HookMsg::StakingTokens {
+            lock_duration: None,
+        })
+        .unwrap(),
+    });
+    let info = mock_info(TEST_ANCHOR_TOKEN, &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::InsufficientFunds {}) => (),
+        _ => panic!("Must return error"),
+    }
+}
+
+#[test]
+pub fn proper_receive_with_dev_fee() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    let transfer_amount_alice = Uint128::from(1_000_000u128);
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_ALICE.to_string(),
+        amount: transfer_amount_alice,
+        msg: to_binary(&Cw20HookMsg::StakingTokens {
+            lock_duration: None,
+        })
+        .unwrap(),
+    });
+    let info = mock_info(TEST_ANCHOR_TOKEN, &[]);
+
+    // deposit MOCK_CONTRACT_ADDR some tokens.
+    let transfer_contract_amount1 = Uint128::from(2_000_000u128);
+    deps.querier.with_token_balances(&[(
+        &TEST_ANCHOR_TOKEN.to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &transfer_contract_amount1)],
+    )]);
+
+    deps.querier.with_gov_stakers(&[(
+        &TEST_ANCHOR_GOV.to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &StakerResponse {
+                balance: Uint128::zero(),
+                share: Uint128::zero(),
+                locked_balance: vec![],
+            },
+        )],
+    )]);
+
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let msg = res.messages.first().expect("no message");
+    // assert for Send to TEST_ANCHOR_GOV SubMsg
+    assert_eq!(
+        msg,
+        &SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: TEST_ANCHOR_TOKEN.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Send {
+                contract: TEST_ANCHOR_GOV.to_string(),
+                amount: transfer_contract_amount1,
+                msg: to_binary(&GovCw20HookMsg::StakeVotingTokens {}).unwrap(),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))
+    );
+
+    //dev check
+    let key = deps.api.addr_canonicalize(TEST_DEV).unwrap();
+    let dev_shares = user_states_read(deps.as_ref().storage)
+        .may_load(key.as_slice())
+        .unwrap_or_default()
         .unwrap_or_default();
 
     let feerate = feerate_read(deps.as_ref().storage).load().unwrap();
@@ -427,7 +927,7 @@ pub fn proper_receive_with_dev_fee() {
     // user check
     let key = deps.api.addr_canonicalize(TEST_ALICE).unwrap();
     let user_shares = user_states_read(deps.as_ref().storage)
-        .may_load(&key.as_slice())
+        .may_load(key.as_slice())
         .unwrap_or_default()
         .unwrap_or_default();
 
@@ -443,6 +943,121 @@ pub fn proper_receive_with_dev_fee() {
     assert_eq!(total_shares, user_shares + dev_shares);
 }
 
+#[test]
+pub fn proper_receive_with_claimable_dev_fee() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    let msg = ExecuteMsg::UpdateFeeMode {
+        fee_mode: FeeMode::Claimable,
+    };
+    let info = mock_info(TEST_ALICE, &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
+    match res {
+        Err(ContractError::Unauthorized {}) => {}
+        _ => panic!("Must return Unauthorized error"),
+    }
+    let info = mock_info(TEST_CREATOR, &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let transfer_amount_alice = Uint128::from(1_000_000u128);
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_ALICE.to_string(),
+        amount: transfer_amount_alice,
+        msg: to_binary(&Cw20HookMsg::StakingTokens {
+            lock_duration: None,
+        })
+        .unwrap(),
+    });
+    let info = mock_info(TEST_ANCHOR_TOKEN, &[]);
+
+    // deposit MOCK_CONTRACT_ADDR some tokens.
+    let transfer_contract_amount1 = Uint128::from(2_000_000u128);
+    deps.querier.with_token_balances(&[(
+        &TEST_ANCHOR_TOKEN.to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &transfer_contract_amount1)],
+    )]);
+
+    deps.querier.with_gov_stakers(&[(
+        &TEST_ANCHOR_GOV.to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &StakerResponse {
+                balance: Uint128::zero(),
+                share: Uint128::zero(),
+                locked_balance: vec![],
+            },
+        )],
+    )]);
+
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let feerate = feerate_read(deps.as_ref().storage).load().unwrap();
+    let dev_amount = feerate * transfer_amount_alice;
+
+    // the dev fee is kept out of gov
+    let msg = res.messages.first().expect("no message");
+    assert_eq!(
+        msg,
+        &SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: TEST_ANCHOR_TOKEN.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Send {
+                contract: TEST_ANCHOR_GOV.to_string(),
+                amount: transfer_contract_amount1 - dev_amount,
+                msg: to_binary(&GovCw20HookMsg::StakeVotingTokens {}).unwrap(),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))
+    );
+
+    //dev check
+    let key = deps.api.addr_canonicalize(TEST_DEV).unwrap();
+    let dev_shares = user_states_read(deps.as_ref().storage)
+        .may_load(key.as_slice())
+        .unwrap();
+    assert_eq!(dev_shares, None);
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::State {}).unwrap();
+    let state_response: StateResponse = from_binary(&res).unwrap();
+    assert_eq!(state_response.fee_mode, FeeMode::Claimable);
+    assert_eq!(state_response.claimable_fees, dev_amount);
+
+    // user check
+    let key = deps.api.addr_canonicalize(TEST_ALICE).unwrap();
+    let user_shares = user_states_read(deps.as_ref().storage)
+        .load(key.as_slice())
+        .unwrap();
+    let total_shares = total_shares_read(deps.as_ref().storage).load().unwrap();
+    assert_eq!(user_shares, transfer_amount_alice - dev_amount);
+    assert_eq!(total_shares, user_shares);
+
+    // only dev claims
+    let msg = ExecuteMsg::ClaimFees {};
+    let info = mock_info(TEST_ALICE, &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
+    match res {
+        Err(ContractError::Unauthorized {}) => {}
+        _ => panic!("Must return Unauthorized error"),
+    }
+    let info = mock_info(TEST_DEV, &[]);
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: TEST_ANCHOR_TOKEN.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: TEST_DEV.to_string(),
+                amount: dev_amount,
+            })
+            .unwrap(),
+            funds: vec![],
+        }))]
+    );
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::NothingToClaim {}) => {}
+        _ => panic!("Must return NothingToClaim error"),
+    }
+}
+
 #[test]
 pub fn proper_receive_with_dev_fee_same_account() {
     let mut deps = mock_dependencies(&[]);
@@ -451,9 +1066,12 @@ pub fn proper_receive_with_dev_fee_same_account() {
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: TEST_DEV.to_string(),
         amount: transfer_amount_alice,
-        msg: to_binary(&Cw20HookMsg::StakingTokens {}).unwrap(),
+        msg: to_binary(&Cw20HookMsg::StakingTokens {
+            lock_duration: None,
+        })
+        .unwrap(),
     });
-    let info = mock_info(&(TEST_ANCHOR_TOKEN.to_string()), &[]);
+    let info = mock_info(TEST_ANCHOR_TOKEN, &[]);
 
     // deposit MOCK_CONTRACT_ADDR some tokens.
     let transfer_contract_amount1 = Uint128::from(2_000_000u128);
@@ -475,7 +1093,7 @@ pub fn proper_receive_with_dev_fee_same_account() {
     )]);
 
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
-    let msg = res.messages.get(0).expect("no message");
+    let msg = res.messages.first().expect("no message");
     // assert for Send to TEST_ANCHOR_GOV SubMsg
     assert_eq!(
         msg,
@@ -494,7 +1112,7 @@ pub fn proper_receive_with_dev_fee_same_account() {
     // user check
     let key = deps.api.addr_canonicalize(TEST_DEV).unwrap();
     let user_shares = user_states_read(deps.as_ref().storage)
-        .may_load(&key.as_slice())
+        .may_load(key.as_slice())
         .unwrap_or_default()
         .unwrap_or_default();
 
@@ -511,6 +1129,8 @@ fn proper_receive_without_dev_fee_double() {
         dev: TEST_DEV.to_string(),
         anchor_token: TEST_ANCHOR_TOKEN.to_string(),
         anchor_gov: TEST_ANCHOR_GOV.to_string(),
+        gov_kind: None,
+        owner: None,
     };
 
     let info = mock_info(TEST_CREATOR, &[]);
@@ -521,14 +1141,17 @@ fn proper_receive_without_dev_fee_double() {
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: TEST_ALICE.to_string(),
         amount: transfer_amount_alice,
-        msg: to_binary(&Cw20HookMsg::StakingTokens {}).unwrap(),
+        msg: to_binary(&Cw20HookMsg::StakingTokens {
+            lock_duration: None,
+        })
+        .unwrap(),
     });
 
-    // let a: Binary = to_binary(&Cw20HookMsg::StakingTokens {}).unwrap();
+    // let a: Binary = to_binary(&Cw20HookMsg::StakingTokens { lock_duration: None }).unwrap();
     // let b = String::from_utf8(to_vec(&a).unwrap()).unwrap();
-    // let c = String::from_utf8(to_vec(&Cw20HookMsg::StakingTokens {}).unwrap()).unwrap();
+    // let c = String::from_utf8(to_vec(&Cw20HookMsg::StakingTokens { lock_duration: None }).unwrap()).unwrap();
     // println!("{},{},{}", a.to_base64(), b, c);
-    let info = mock_info(&(TEST_ANCHOR_TOKEN.to_string()), &[]);
+    let info = mock_info(TEST_ANCHOR_TOKEN, &[]);
 
     // deposit MOCK_CONTRACT_ADDR some tokens.
     let transfer_contract_amount1 = Uint128::from(2_000_000u128);
@@ -550,7 +1173,7 @@ fn proper_receive_without_dev_fee_double() {
     )]);
 
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
-    let msg = res.messages.get(0).expect("no message");
+    let msg = res.messages.first().expect("no message");
     // assert for Send to TEST_ANCHOR_GOV SubMsg
     assert_eq!(
         msg,
@@ -569,7 +1192,7 @@ fn proper_receive_without_dev_fee_double() {
     // user check
     let key = deps.api.addr_canonicalize(TEST_ALICE).unwrap();
     let user_shares_alice = user_states_read(deps.as_ref().storage)
-        .may_load(&key.as_slice())
+        .may_load(key.as_slice())
         .unwrap_or_default()
         .unwrap_or_default();
 
@@ -607,12 +1230,15 @@ fn proper_receive_without_dev_fee_double() {
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: TEST_BOB.to_string(),
         amount: transfer_amount_bob,
-        msg: to_binary(&Cw20HookMsg::StakingTokens {}).unwrap(),
+        msg: to_binary(&Cw20HookMsg::StakingTokens {
+            lock_duration: None,
+        })
+        .unwrap(),
     });
 
-    let info = mock_info(&(TEST_ANCHOR_TOKEN.to_string()), &[]);
+    let info = mock_info(TEST_ANCHOR_TOKEN, &[]);
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
-    let msg = res.messages.get(0).expect("no message");
+    let msg = res.messages.first().expect("no message");
 
     assert_eq!(
         msg,
@@ -630,7 +1256,7 @@ fn proper_receive_without_dev_fee_double() {
 
     let key = deps.api.addr_canonicalize(TEST_BOB).unwrap();
     let user_shares_bob = user_states_read(deps.as_ref().storage)
-        .may_load(&key.as_slice())
+        .may_load(key.as_slice())
         .unwrap_or_default()
         .unwrap_or_default();
 
@@ -644,103 +1270,326 @@ fn proper_receive_without_dev_fee_double() {
 }
 
 #[test]
-fn fails_withdraw_token_out_of_amount() {
+fn fails_update_deposit_limits_with_unauthorized() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    let msg = ExecuteMsg::UpdateDepositLimits {
+        max_total_deposit: Some(Uint128::from(100u128)),
+        max_per_user: None,
+    };
+    let info = mock_info(TEST_DEV, &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("Must return error"),
+    }
+}
+
+#[test]
+fn fails_receive_over_deposit_limits() {
     let mut deps = mock_dependencies(&[]);
     mock_instantiate(deps.as_mut());
 
-    let user_shares = Uint128::from(1000u128);
+    // alice already owns half of the pool.
     let key = deps.api.addr_canonicalize(TEST_ALICE).unwrap();
-    let balance = Uint128::from(100_000_000u128);
-    let share = Uint128::from(50_000_000u128);
     user_states_store(deps.as_mut().storage)
-        .save(&key, &user_shares)
+        .save(&key, &Uint128::from(500u128))
         .unwrap();
     total_shares_store(deps.as_mut().storage)
-        .save(&(balance / Uint128::from(2u128)))
+        .save(&Uint128::from(1000u128))
         .unwrap();
-
     deps.querier.with_gov_stakers(&[(
         &TEST_ANCHOR_GOV.to_string(),
         &[(
             &MOCK_CONTRACT_ADDR.to_string(),
             &StakerResponse {
-                balance: balance,
-                share: share,
+                balance: Uint128::from(2000u128),
+                share: Uint128::from(1000u128),
                 locked_balance: vec![],
             },
         )],
     )]);
 
-    let msg = QueryMsg::UserState {
-        user: TEST_ALICE.to_string(),
+    let msg = ExecuteMsg::UpdateDepositLimits {
+        max_total_deposit: Some(Uint128::from(3000u128)),
+        max_per_user: Some(Uint128::from(1500u128)),
     };
-    let res = query(deps.as_ref(), mock_env(), msg).unwrap();
-    let user_state_response: UserStateResponse = from_binary(&res).unwrap();
-    let user_balance = user_state_response.available_balance;
+    let info = mock_info(TEST_CREATOR, &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-    // checkout user share and amount
-    assert_eq!(user_state_response.shares, user_shares);
+    let msg = QueryMsg::DepositHeadroom {
+        user: Some(TEST_ALICE.to_string()),
+    };
+    let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+    let headroom: DepositHeadroomResponse = from_binary(&res).unwrap();
     assert_eq!(
-        user_state_response.available_balance,
-        user_shares * Uint128::from(2u128)
+        headroom,
+        DepositHeadroomResponse {
+            max_total_deposit: Some(Uint128::from(3000u128)),
+            max_per_user: Some(Uint128::from(1500u128)),
+            total_headroom: Some(Uint128::from(1000u128)),
+            user_headroom: Some(Uint128::from(500u128)),
+        }
     );
-    // try to withdraw out of balance
-    let msg = ExecuteMsg::WithdrawToken {
-        amount: Some(user_balance + Uint128::from(2u128)),
-    };
 
-    let info = mock_info(TEST_ALICE, &[]);
-    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+    // alice holds 1000 already, 501 is over per user cap.
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_ALICE.to_string(),
+        amount: Uint128::from(501u128),
+        msg: to_binary(&Cw20HookMsg::StakingTokens {
+            lock_duration: None,
+        })
+        .unwrap(),
+    });
+    let info = mock_info(TEST_ANCHOR_TOKEN, &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
     match res {
-        Err(ContractError::InsufficientFunds {}) => (),
+        Err(ContractError::UserDepositCapExceeded {}) => (),
         _ => panic!("Must return error"),
     }
-}
-#[test]
-fn fails_withdraw_token_without_deposit() {
-    let mut deps = mock_dependencies(&[]);
-    mock_instantiate(deps.as_mut());
-
-    total_shares_store(deps.as_mut().storage)
-        .save(&Uint128::from(1000u128))
-        .unwrap();
-
-    // try to withdraw out of balance
-    let msg = ExecuteMsg::WithdrawToken {
-        amount: Some(Uint128::from(1u128)),
-    };
 
-    let info = mock_info(TEST_ALICE, &[]);
-    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+    // bob is only bound by the total cap.
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_BOB.to_string(),
+        amount: Uint128::from(1001u128),
+        msg: to_binary(&Cw20HookMsg::StakingTokens {
+            lock_duration: None,
+        })
+        .unwrap(),
+    });
+    let info = mock_info(TEST_ANCHOR_TOKEN, &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
     match res {
-        Err(ContractError::NothingStaked {}) => (),
+        Err(ContractError::DepositCapExceeded {}) => (),
         _ => panic!("Must return error"),
     }
 }
 
 #[test]
-fn proper_withdraw_token() {
+fn fails_receive_not_allowlisted() {
     let mut deps = mock_dependencies(&[]);
     mock_instantiate(deps.as_mut());
 
-    let user_shares = Uint128::from(1000u128);
-    let key = deps.api.addr_canonicalize(TEST_ALICE).unwrap();
-    user_states_store(deps.as_mut().storage)
-        .save(&key, &user_shares)
-        .unwrap();
-    total_shares_store(deps.as_mut().storage)
-        .save(&user_shares)
-        .unwrap();
-
+    let info = mock_info(TEST_DEV, &[]);
+    let msg = ExecuteMsg::SetAllowlistEnabled { enabled: true };
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("Must return error"),
+    }
+
+    let info = mock_info(TEST_CREATOR, &[]);
+    let msg = ExecuteMsg::SetAllowlistEnabled { enabled: true };
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_ALICE.to_string(),
+        amount: Uint128::from(11u128),
+        msg: to_binary(&Cw20HookMsg::StakingTokens {
+            lock_duration: None,
+        })
+        .unwrap(),
+    });
+    let info = mock_info(TEST_ANCHOR_TOKEN, &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::NotAllowlisted {}) => (),
+        _ => panic!("Must return error"),
+    }
+}
+
+#[test]
+fn proper_update_allowlist() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+
+    let info = mock_info(TEST_CREATOR, &[]);
+    let msg = ExecuteMsg::SetAllowlistEnabled { enabled: true };
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let info = mock_info(TEST_CREATOR, &[]);
+    let msg = ExecuteMsg::UpdateAllowlist {
+        add: vec![
+            TEST_ALICE.to_string(),
+            TEST_BOB.to_string(),
+            TEST_DEV2.to_string(),
+        ],
+        remove: vec![TEST_DEV2.to_string()],
+    };
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = QueryMsg::Allowlist {
+        start_after: None,
+        limit: Some(1),
+    };
+    let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+    let allowlist: AllowlistResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        allowlist,
+        AllowlistResponse {
+            enabled: true,
+            addresses: vec![TEST_ALICE.to_string()],
+        }
+    );
+    let msg = QueryMsg::Allowlist {
+        start_after: Some(TEST_ALICE.to_string()),
+        limit: None,
+    };
+    let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+    let allowlist: AllowlistResponse = from_binary(&res).unwrap();
+    assert_eq!(allowlist.addresses, vec![TEST_BOB.to_string()]);
+
+    // alice is allowed to deposit
+    deps.querier.with_token_balances(&[(
+        &TEST_ANCHOR_TOKEN.to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(11u128))],
+    )]);
+    deps.querier.with_gov_stakers(&[(
+        &TEST_ANCHOR_GOV.to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &StakerResponse {
+                balance: Uint128::zero(),
+                share: Uint128::zero(),
+                locked_balance: vec![],
+            },
+        )],
+    )]);
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_ALICE.to_string(),
+        amount: Uint128::from(11u128),
+        msg: to_binary(&Cw20HookMsg::StakingTokens {
+            lock_duration: None,
+        })
+        .unwrap(),
+    });
+    let info = mock_info(TEST_ANCHOR_TOKEN, &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // removed holders can still withdraw
+    let info = mock_info(TEST_CREATOR, &[]);
+    let msg = ExecuteMsg::UpdateAllowlist {
+        add: vec![],
+        remove: vec![TEST_ALICE.to_string()],
+    };
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    deps.querier.with_gov_stakers(&[(
+        &TEST_ANCHOR_GOV.to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &StakerResponse {
+                balance: Uint128::from(11u128),
+                share: Uint128::from(11u128),
+                locked_balance: vec![],
+            },
+        )],
+    )]);
+    let info = mock_info(TEST_ALICE, &[]);
+    let msg = ExecuteMsg::WithdrawToken { amount: None };
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(1, res.messages.len());
+}
+
+#[test]
+fn fails_update_fee_exemptions_with_unauthorized() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    let msg = ExecuteMsg::UpdateFeeExemptions {
+        add: vec![TEST_ALICE.to_string()],
+        remove: vec![],
+    };
+    let info = mock_info(TEST_DEV, &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("Must return error"),
+    }
+}
+
+#[test]
+fn proper_receive_with_fee_exemption() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    let msg = ExecuteMsg::UpdateFeeExemptions {
+        add: vec![TEST_ALICE.to_string()],
+        remove: vec![],
+    };
+    let info = mock_info(TEST_CREATOR, &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = QueryMsg::FeeExemptions {
+        start_after: None,
+        limit: None,
+    };
+    let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+    let exemptions: FeeExemptionsResponse = from_binary(&res).unwrap();
+    assert_eq!(exemptions.addresses, vec![TEST_ALICE.to_string()]);
+
+    let transfer_amount_alice = Uint128::from(1_000_000u128);
+    deps.querier.with_token_balances(&[(
+        &TEST_ANCHOR_TOKEN.to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &transfer_amount_alice)],
+    )]);
+    deps.querier.with_gov_stakers(&[(
+        &TEST_ANCHOR_GOV.to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &StakerResponse {
+                balance: Uint128::zero(),
+                share: Uint128::zero(),
+                locked_balance: vec![],
+            },
+        )],
+    )]);
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_ALICE.to_string(),
+        amount: transfer_amount_alice,
+        msg: to_binary(&Cw20HookMsg::StakingTokens {
+            lock_duration: None,
+        })
+        .unwrap(),
+    });
+    let info = mock_info(TEST_ANCHOR_TOKEN, &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert!(res
+        .attributes
+        .contains(&Attribute::new("fee_exempt", "true")));
+
+    // no dev share, alice get the full amount
+    let key = deps.api.addr_canonicalize(TEST_DEV).unwrap();
+    let dev_shares = user_states_read(deps.as_ref().storage)
+        .may_load(key.as_slice())
+        .unwrap();
+    assert_eq!(dev_shares, None);
+    let key = deps.api.addr_canonicalize(TEST_ALICE).unwrap();
+    let user_shares = user_states_read(deps.as_ref().storage)
+        .load(key.as_slice())
+        .unwrap();
+    assert_eq!(user_shares, transfer_amount_alice);
+}
+
+#[test]
+fn fails_withdraw_token_out_of_amount() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+
+    let user_shares = Uint128::from(1000u128);
+    let key = deps.api.addr_canonicalize(TEST_ALICE).unwrap();
     let balance = Uint128::from(100_000_000u128);
     let share = Uint128::from(50_000_000u128);
+    user_states_store(deps.as_mut().storage)
+        .save(&key, &user_shares)
+        .unwrap();
+    total_shares_store(deps.as_mut().storage)
+        .save(&(balance / Uint128::from(2u128)))
+        .unwrap();
+
     deps.querier.with_gov_stakers(&[(
         &TEST_ANCHOR_GOV.to_string(),
         &[(
             &MOCK_CONTRACT_ADDR.to_string(),
             &StakerResponse {
-                balance: balance,
-                share: share,
+                balance,
+                share,
                 locked_balance: vec![],
             },
         )],
@@ -751,38 +1600,50 @@ fn proper_withdraw_token() {
     };
     let res = query(deps.as_ref(), mock_env(), msg).unwrap();
     let user_state_response: UserStateResponse = from_binary(&res).unwrap();
+    let user_balance = user_state_response.available_balance;
 
     // checkout user share and amount
     assert_eq!(user_state_response.shares, user_shares);
-    assert_eq!(user_state_response.available_balance, balance);
+    assert_eq!(
+        user_state_response.available_balance,
+        user_shares * Uint128::from(2u128)
+    );
+    // try to withdraw out of balance
+    let msg = ExecuteMsg::WithdrawToken {
+        amount: Some(user_balance + Uint128::from(2u128)),
+    };
 
-    // try to withdraw a few
-    let withdraw_amount = balance / Uint128::from(2u128);
+    let info = mock_info(TEST_ALICE, &[]);
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+    match res {
+        Err(ContractError::InsufficientFunds {}) => (),
+        _ => panic!("Must return error"),
+    }
+}
+#[test]
+fn fails_withdraw_token_without_deposit() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+
+    total_shares_store(deps.as_mut().storage)
+        .save(&Uint128::from(1000u128))
+        .unwrap();
+
+    // try to withdraw out of balance
     let msg = ExecuteMsg::WithdrawToken {
-        amount: Some(withdraw_amount),
+        amount: Some(Uint128::from(1u128)),
     };
 
     let info = mock_info(TEST_ALICE, &[]);
-    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
-    // assert for Send to TEST_ANCHOR_GOV SubMsg
-    assert_eq!(
-        res,
-        Response::new().add_submessage(SubMsg::reply_on_success(
-            CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: TEST_ANCHOR_GOV.to_string(),
-                msg: to_binary(&GovExcuteMsg::WithdrawVotingTokens {
-                    amount: Some(withdraw_amount)
-                })
-                .unwrap(),
-                funds: vec![],
-            }),
-            WITHDRAW_REPLY_ID
-        ))
-    )
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+    match res {
+        Err(ContractError::NothingStaked {}) => (),
+        _ => panic!("Must return error"),
+    }
 }
 
 #[test]
-fn proper_withdraw_token_all() {
+fn proper_withdraw_token() {
     let mut deps = mock_dependencies(&[]);
     mock_instantiate(deps.as_mut());
 
@@ -802,8 +1663,8 @@ fn proper_withdraw_token_all() {
         &[(
             &MOCK_CONTRACT_ADDR.to_string(),
             &StakerResponse {
-                balance: balance,
-                share: share,
+                balance,
+                share,
                 locked_balance: vec![],
             },
         )],
@@ -820,71 +1681,274 @@ fn proper_withdraw_token_all() {
     assert_eq!(user_state_response.available_balance, balance);
 
     // try to withdraw a few
-    let msg = ExecuteMsg::WithdrawToken { amount: None };
+    let withdraw_amount = balance / Uint128::from(2u128);
+    let msg = ExecuteMsg::WithdrawToken {
+        amount: Some(withdraw_amount),
+    };
 
     let info = mock_info(TEST_ALICE, &[]);
     let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
-    let msg = res.messages.get(0).expect("no message");
     // assert for Send to TEST_ANCHOR_GOV SubMsg
     assert_eq!(
-        msg,
-        &SubMsg::reply_on_success(
-            CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: TEST_ANCHOR_GOV.to_string(),
-                msg: to_binary(&GovExcuteMsg::WithdrawVotingTokens {
-                    amount: Some(balance)
-                })
-                .unwrap(),
-                funds: vec![],
-            }),
-            WITHDRAW_REPLY_ID
-        )
-    );
+        res,
+        Response::new()
+            .add_attribute("method", "withdraw_token")
+            .add_attribute("exit_fee", "0")
+            .add_attribute("full_exit", "false")
+            .add_submessage(SubMsg::reply_on_success(
+                CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: TEST_ANCHOR_GOV.to_string(),
+                    msg: to_binary(&GovExcuteMsg::WithdrawVotingTokens {
+                        amount: Some(withdraw_amount)
+                    })
+                    .unwrap(),
+                    funds: vec![],
+                }),
+                WITHDRAW_REPLY_ID
+            ))
+    )
 }
 
 #[test]
-fn fails_reply_temp_send_data_not_found() {
+fn proper_withdraw_token_with_exit_fee() {
     let mut deps = mock_dependencies(&[]);
     mock_instantiate(deps.as_mut());
-    let reply_msg = Reply {
-        id: WITHDRAW_REPLY_ID,
-        result: ContractResult::Ok(SubMsgExecutionResponse {
-            events: vec![],
-            data: None,
-        }),
+    let info = mock_info(TEST_CREATOR, &[]);
+    let msg = ExecuteMsg::UpdateExitFee {
+        max_feerate: Decimal::percent(10),
+        duration: u64::MAX,
     };
-    let res = reply(deps.as_mut(), mock_env(), reply_msg);
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
     match res {
-        Err(ContractError::Std(StdError::NotFound { kind, .. })) => {
-            assert_eq!(kind, "staking_anchor_gov::state::TempSendData")
-        }
-        _ => panic!("Must return error"),
+        Err(ContractError::DurationTooLong {}) => {}
+        _ => panic!("Must return DurationTooLong error"),
     }
-}
-
-#[test]
-fn proper_reply() {
-    let mut deps = mock_dependencies(&[]);
-    mock_instantiate(deps.as_mut());
-    temp_send_store(&mut deps.storage)
-        .save(&TempSendData {
-            recipient: TEST_CREATOR.to_string(),
-            amount: Uint128::from(100u128),
-        })
-        .unwrap();
-    let reply_msg = Reply {
-        id: WITHDRAW_REPLY_ID,
-        result: ContractResult::Ok(SubMsgExecutionResponse {
-            events: vec![],
-            data: None,
-        }),
+    let msg = ExecuteMsg::UpdateExitFee {
+        max_feerate: Decimal::percent(10),
+        duration: 1000,
     };
-    let res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
-    let msg = res.messages.get(0).expect("no message");
-    assert_eq!(
-        msg,
-        &SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: TEST_ANCHOR_TOKEN.to_string(),
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // alice deposits 1000, bob owns the other half of the pool
+    let mut env = mock_env();
+    deps.querier.with_token_balances(&[(
+        &TEST_ANCHOR_TOKEN.to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(1000u128))],
+    )]);
+    deps.querier.with_gov_stakers(&[(
+        &TEST_ANCHOR_GOV.to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &StakerResponse {
+                balance: Uint128::zero(),
+                share: Uint128::zero(),
+                locked_balance: vec![],
+            },
+        )],
+    )]);
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_ALICE.to_string(),
+        amount: Uint128::from(1000u128),
+        msg: to_binary(&Cw20HookMsg::StakingTokens {
+            lock_duration: None,
+        })
+        .unwrap(),
+    });
+    let info = mock_info(TEST_ANCHOR_TOKEN, &[]);
+    execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    let key = deps.api.addr_canonicalize(TEST_ALICE).unwrap();
+    user_states_store(deps.as_mut().storage)
+        .save(&key, &Uint128::from(1000u128))
+        .unwrap();
+    total_shares_store(deps.as_mut().storage)
+        .save(&Uint128::from(2000u128))
+        .unwrap();
+    deps.querier.with_gov_stakers(&[(
+        &TEST_ANCHOR_GOV.to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &StakerResponse {
+                balance: Uint128::from(2000u128),
+                share: Uint128::from(2000u128),
+                locked_balance: vec![],
+            },
+        )],
+    )]);
+
+    // a quarter of the fee period is gone
+    env.block.time = env.block.time.plus_seconds(250);
+    let msg = QueryMsg::UserState {
+        user: TEST_ALICE.to_string(),
+    };
+    let res = query(deps.as_ref(), env.clone(), msg).unwrap();
+    let user_state_response: UserStateResponse = from_binary(&res).unwrap();
+    assert_eq!(user_state_response.seconds_to_free_exit, 750);
+
+    let msg = ExecuteMsg::WithdrawToken { amount: None };
+    let info = mock_info(TEST_ALICE, &[]);
+    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    // 1000 * 10% * 750 / 1000 stays in the pool
+    assert!(res.attributes.contains(&Attribute::new("exit_fee", "75")));
+    assert_eq!(
+        res.messages[0],
+        SubMsg::reply_on_success(
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: TEST_ANCHOR_GOV.to_string(),
+                msg: to_binary(&GovExcuteMsg::WithdrawVotingTokens {
+                    amount: Some(Uint128::from(925u128))
+                })
+                .unwrap(),
+                funds: vec![],
+            }),
+            WITHDRAW_REPLY_ID
+        )
+    );
+    let total_shares = total_shares_read(deps.as_ref().storage).load().unwrap();
+    assert_eq!(total_shares, Uint128::from(1000u128));
+
+    // free after the period
+    env.block.time = env.block.time.plus_seconds(750);
+    let msg = QueryMsg::UserState {
+        user: TEST_ALICE.to_string(),
+    };
+    let res = query(deps.as_ref(), env, msg).unwrap();
+    let user_state_response: UserStateResponse = from_binary(&res).unwrap();
+    assert_eq!(user_state_response.seconds_to_free_exit, 0);
+}
+
+#[test]
+fn proper_withdraw_token_all() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+
+    let user_shares = Uint128::from(1000u128);
+    let key = deps.api.addr_canonicalize(TEST_ALICE).unwrap();
+    user_states_store(deps.as_mut().storage)
+        .save(&key, &user_shares)
+        .unwrap();
+    total_shares_store(deps.as_mut().storage)
+        .save(&user_shares)
+        .unwrap();
+
+    let balance = Uint128::from(100_000_000u128);
+    let share = Uint128::from(50_000_000u128);
+    deps.querier.with_gov_stakers(&[(
+        &TEST_ANCHOR_GOV.to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &StakerResponse {
+                balance,
+                share,
+                locked_balance: vec![],
+            },
+        )],
+    )]);
+
+    let msg = QueryMsg::UserState {
+        user: TEST_ALICE.to_string(),
+    };
+    let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+    let user_state_response: UserStateResponse = from_binary(&res).unwrap();
+
+    // checkout user share and amount
+    assert_eq!(user_state_response.shares, user_shares);
+    assert_eq!(user_state_response.available_balance, balance);
+
+    // try to withdraw a few
+    let msg = ExecuteMsg::WithdrawToken { amount: None };
+
+    let info = mock_info(TEST_ALICE, &[]);
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+    let msg = res.messages.first().expect("no message");
+    // assert for Send to TEST_ANCHOR_GOV SubMsg
+    assert_eq!(
+        msg,
+        &SubMsg::reply_on_success(
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: TEST_ANCHOR_GOV.to_string(),
+                msg: to_binary(&GovExcuteMsg::WithdrawVotingTokens {
+                    amount: Some(balance)
+                })
+                .unwrap(),
+                funds: vec![],
+            }),
+            WITHDRAW_REPLY_ID
+        )
+    );
+    assert!(res
+        .attributes
+        .contains(&Attribute::new("full_exit", "true")));
+
+    // the entry is removed, nothing left to withdraw
+    let key = deps.api.addr_canonicalize(TEST_ALICE).unwrap();
+    let user_shares = user_states_read(deps.as_ref().storage)
+        .may_load(key.as_slice())
+        .unwrap();
+    assert_eq!(user_shares, None);
+    let msg = ExecuteMsg::WithdrawToken { amount: None };
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::NothingStaked {}) => {}
+        _ => panic!("Must return NothingStaked error"),
+    }
+
+    // a zero share entry left by older versions is treated the same way
+    user_states_store(deps.as_mut().storage)
+        .save(key.as_slice(), &Uint128::zero())
+        .unwrap();
+    let msg = ExecuteMsg::WithdrawToken { amount: None };
+    let info = mock_info(TEST_ALICE, &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::NothingStaked {}) => {}
+        _ => panic!("Must return NothingStaked error"),
+    }
+}
+
+#[test]
+fn fails_reply_temp_send_data_not_found() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    let reply_msg = Reply {
+        id: WITHDRAW_REPLY_ID,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: None,
+        }),
+    };
+    let res = reply(deps.as_mut(), mock_env(), reply_msg);
+    match res {
+        Err(ContractError::Std(StdError::NotFound { kind, .. })) => {
+            assert_eq!(kind, "staking_anchor_gov::state::TempSendData")
+        }
+        _ => panic!("Must return error"),
+    }
+}
+
+#[test]
+fn proper_reply() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    temp_send_store(&mut deps.storage)
+        .save(&TempSendData {
+            recipient: TEST_CREATOR.to_string(),
+            amount: Uint128::from(100u128),
+        })
+        .unwrap();
+    let reply_msg = Reply {
+        id: WITHDRAW_REPLY_ID,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: None,
+        }),
+    };
+    let res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+    let msg = res.messages.first().expect("no message");
+    assert_eq!(
+        msg,
+        &SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: TEST_ANCHOR_TOKEN.to_string(),
             msg: to_binary(&Cw20ExecuteMsg::Transfer {
                 recipient: TEST_CREATOR.to_string(),
                 amount: Uint128::from(100u128),
@@ -913,6 +1977,10 @@ fn query_config() {
             dev: TEST_DEV.to_string(),
             owner: TEST_CREATOR.to_string(),
             pendding_owner: String::default(),
+            pendding_owner_expires: None,
+            guardian: String::default(),
+            pendding_guardian: String::default(),
+            gov_kind: GovKind::Anchor,
         }
     );
 
@@ -929,6 +1997,10 @@ fn query_config() {
             dev: TEST_DEV.to_string(),
             owner: TEST_CREATOR.to_string(),
             pendding_owner: TEST_DEV2.to_string(),
+            pendding_owner_expires: Some(Expiration::AtHeight(mock_env().block.height + 100)),
+            guardian: String::default(),
+            pendding_guardian: String::default(),
+            gov_kind: GovKind::Anchor,
         }
     );
 }
@@ -937,7 +2009,7 @@ fn query_user_state() {
     let mut deps = mock_dependencies(&[]);
     mock_instantiate(deps.as_mut());
     let transfer_amount_alice = Uint128::from(1_000_000u128);
-    let info = mock_info(&(TEST_ANCHOR_TOKEN.to_string()), &[]);
+    let info = mock_info(TEST_ANCHOR_TOKEN, &[]);
 
     // query empty UserState
     let msg = QueryMsg::UserState {
@@ -952,6 +2024,7 @@ fn query_user_state() {
             available_balance: Uint128::zero(),
             shares: Uint128::zero(),
             locked_balance: Uint128::zero(),
+            seconds_to_free_exit: 0,
         }
     );
 
@@ -1014,7 +2087,10 @@ fn query_user_state() {
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: TEST_ALICE.to_string(),
         amount: transfer_amount_alice,
-        msg: to_binary(&Cw20HookMsg::StakingTokens {}).unwrap(),
+        msg: to_binary(&Cw20HookMsg::StakingTokens {
+            lock_duration: None,
+        })
+        .unwrap(),
     });
     execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
@@ -1023,7 +2099,7 @@ fn query_user_state() {
     let total_shares = total_shares_read(deps.as_ref().storage).load().unwrap();
     let key = deps.api.addr_canonicalize(TEST_ALICE).unwrap();
     let user_shares = user_states_read(deps.as_ref().storage)
-        .may_load(&key.as_slice())
+        .may_load(key.as_slice())
         .unwrap_or_default()
         .unwrap_or_default();
 
@@ -1043,7 +2119,8 @@ fn query_user_state() {
         UserStateResponse {
             available_balance: (balance - Uint128::from(300u128)) * user_shares / total_shares,
             shares: user_shares,
-            locked_balance: Uint128::from(300u128) * user_shares / total_shares
+            locked_balance: Uint128::from(300u128) * user_shares / total_shares,
+            seconds_to_free_exit: 0,
         }
     )
 }
@@ -1102,6 +2179,2962 @@ fn query_state() {
             locked_balance: Uint128::from(300u128),
             available_balance: balance - Uint128::from(300u128),
             total_shares: Uint128::zero(),
+            paused: false,
+            fee_tiers: vec![],
+            fee_mode: FeeMode::Shares,
+            claimable_fees: Uint128::zero(),
+            stray_anc_policy: StrayAncPolicy::CreditPool,
+            compound_incentive: Decimal::zero(),
+        }
+    );
+}
+
+#[test]
+fn query_deposit_headroom() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    deps.querier.with_gov_stakers(&[(
+        &TEST_ANCHOR_GOV.to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &StakerResponse {
+                balance: Uint128::from(2000u128),
+                share: Uint128::from(1000u128),
+                locked_balance: vec![],
+            },
+        )],
+    )]);
+
+    // unlimited by default
+    let msg = QueryMsg::DepositHeadroom {
+        user: Some(TEST_ALICE.to_string()),
+    };
+    let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+    let headroom: DepositHeadroomResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        headroom,
+        DepositHeadroomResponse {
+            max_total_deposit: None,
+            max_per_user: None,
+            total_headroom: None,
+            user_headroom: None,
         }
     );
+
+    // headroom never underflows
+    let msg = ExecuteMsg::UpdateDepositLimits {
+        max_total_deposit: Some(Uint128::from(1000u128)),
+        max_per_user: None,
+    };
+    let info = mock_info(TEST_CREATOR, &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let msg = QueryMsg::DepositHeadroom { user: None };
+    let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+    let headroom: DepositHeadroomResponse = from_binary(&res).unwrap();
+    assert_eq!(headroom.total_headroom, Some(Uint128::zero()));
+    assert_eq!(headroom.user_headroom, None);
+}
+
+#[test]
+fn fails_receive_with_lock_duration_out_of_limits() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+
+    // locking is disabled by default
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_ALICE.to_string(),
+        amount: Uint128::from(1000u128),
+        msg: to_binary(&Cw20HookMsg::StakingTokens {
+            lock_duration: Some(100),
+        })
+        .unwrap(),
+    });
+    let info = mock_info(TEST_ANCHOR_TOKEN, &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::LockDurationOutOfLimits {}) => {}
+        _ => panic!("Must return LockDurationOutOfLimits error"),
+    }
+
+    let msg = ExecuteMsg::UpdateLockConfig {
+        max_lock_duration: 1000,
+        max_boost: Decimal::one(),
+    };
+    let info = mock_info(TEST_ALICE, &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
+    match res {
+        Err(ContractError::Unauthorized {}) => {}
+        _ => panic!("Must return Unauthorized error"),
+    }
+    let info = mock_info(TEST_CREATOR, &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        ExecuteMsg::UpdateLockConfig {
+            max_lock_duration: u64::MAX,
+            max_boost: Decimal::one(),
+        },
+    );
+    match res {
+        Err(ContractError::DurationTooLong {}) => {}
+        _ => panic!("Must return DurationTooLong error"),
+    }
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        ExecuteMsg::UpdateLockConfig {
+            max_lock_duration: 1000,
+            max_boost: Decimal::percent(101),
+        },
+    );
+    match res {
+        Err(ContractError::BoostOutOfLimits {}) => {}
+        _ => panic!("Must return BoostOutOfLimits error"),
+    }
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_ALICE.to_string(),
+        amount: Uint128::from(1000u128),
+        msg: to_binary(&Cw20HookMsg::StakingTokens {
+            lock_duration: Some(1001),
+        })
+        .unwrap(),
+    });
+    let info = mock_info(TEST_ANCHOR_TOKEN, &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::LockDurationOutOfLimits {}) => {}
+        _ => panic!("Must return LockDurationOutOfLimits error"),
+    }
+}
+
+#[test]
+fn proper_receive_with_lock_duration() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    let msg = ExecuteMsg::UpdateFeeRate {
+        new_feerate: Decimal::zero(),
+    };
+    let info = mock_info(TEST_CREATOR, &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let msg = ExecuteMsg::UpdateLockConfig {
+        max_lock_duration: 1000,
+        max_boost: Decimal::one(),
+    };
+    let info = mock_info(TEST_CREATOR, &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    deps.querier.with_token_balances(&[(
+        &TEST_ANCHOR_TOKEN.to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(1000u128))],
+    )]);
+    deps.querier.with_gov_stakers(&[(
+        &TEST_ANCHOR_GOV.to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &StakerResponse {
+                balance: Uint128::zero(),
+                share: Uint128::zero(),
+                locked_balance: vec![],
+            },
+        )],
+    )]);
+
+    // half of the pool is locked for half of the max duration
+    let mut env = mock_env();
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_ALICE.to_string(),
+        amount: Uint128::from(1000u128),
+        msg: to_binary(&Cw20HookMsg::StakingTokens {
+            lock_duration: Some(500),
+        })
+        .unwrap(),
+    });
+    let info = mock_info(TEST_ANCHOR_TOKEN, &[]);
+    execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    deps.querier.with_gov_stakers(&[(
+        &TEST_ANCHOR_GOV.to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &StakerResponse {
+                balance: Uint128::from(1000u128),
+                share: Uint128::from(1000u128),
+                locked_balance: vec![],
+            },
+        )],
+    )]);
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_ALICE.to_string(),
+        amount: Uint128::from(1000u128),
+        msg: to_binary(&Cw20HookMsg::StakingTokens {
+            lock_duration: None,
+        })
+        .unwrap(),
+    });
+    execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let msg = QueryMsg::Positions {
+        user: TEST_ALICE.to_string(),
+        start_after: None,
+        limit: None,
+    };
+    let res = query(deps.as_ref(), env.clone(), msg).unwrap();
+    let positions_response: PositionsResponse = from_binary(&res).unwrap();
+    assert_eq!(positions_response.positions.len(), 1);
+    let position = &positions_response.positions[0];
+    assert_eq!(position.id, 1);
+    assert_eq!(position.shares, Uint128::from(1000u128));
+    assert_eq!(position.weight, Uint128::from(1500u128));
+    assert_eq!(position.unlock_time, env.block.time.seconds() + 500);
+    assert!(position.locked);
+
+    deps.querier.with_gov_stakers(&[(
+        &TEST_ANCHOR_GOV.to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &StakerResponse {
+                balance: Uint128::from(2000u128),
+                share: Uint128::from(2000u128),
+                locked_balance: vec![],
+            },
+        )],
+    )]);
+
+    // only the unlocked shares can be withdrawn
+    let msg = ExecuteMsg::WithdrawToken {
+        amount: Some(Uint128::from(1500u128)),
+    };
+    let info = mock_info(TEST_ALICE, &[]);
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+    match res {
+        Err(ContractError::SharesLocked {}) => {}
+        _ => panic!("Must return SharesLocked error"),
+    }
+    let msg = ExecuteMsg::WithdrawToken { amount: None };
+    execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
+    let key = deps.api.addr_canonicalize(TEST_ALICE).unwrap();
+    let user_shares = user_states_read(deps.as_ref().storage)
+        .load(key.as_slice())
+        .unwrap();
+    assert_eq!(user_shares, Uint128::from(1000u128));
+
+    deps.querier.with_gov_stakers(&[(
+        &TEST_ANCHOR_GOV.to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &StakerResponse {
+                balance: Uint128::from(1000u128),
+                share: Uint128::from(1000u128),
+                locked_balance: vec![],
+            },
+        )],
+    )]);
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone());
+    match res {
+        Err(ContractError::SharesLocked {}) => {}
+        _ => panic!("Must return SharesLocked error"),
+    }
+
+    // the position is released after the lock ends
+    env.block.time = env.block.time.plus_seconds(500);
+    execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    let msg = QueryMsg::Positions {
+        user: TEST_ALICE.to_string(),
+        start_after: None,
+        limit: None,
+    };
+    let res = query(deps.as_ref(), env, msg).unwrap();
+    let positions_response: PositionsResponse = from_binary(&res).unwrap();
+    assert!(positions_response.positions.is_empty());
+}
+
+#[test]
+fn proper_withdraw_token_with_dev_vesting() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    let msg = ExecuteMsg::UpdateDevVesting { duration: 1000 };
+    let info = mock_info(TEST_ALICE, &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
+    match res {
+        Err(ContractError::Unauthorized {}) => {}
+        _ => panic!("Must return Unauthorized error"),
+    }
+    let info = mock_info(TEST_CREATOR, &[]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        ExecuteMsg::UpdateDevVesting { duration: u64::MAX },
+    );
+    match res {
+        Err(ContractError::DurationTooLong {}) => {}
+        _ => panic!("Must return DurationTooLong error"),
+    }
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let mut env = mock_env();
+    deps.querier.with_token_balances(&[(
+        &TEST_ANCHOR_TOKEN.to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(100_000u128))],
+    )]);
+    deps.querier.with_gov_stakers(&[(
+        &TEST_ANCHOR_GOV.to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &StakerResponse {
+                balance: Uint128::zero(),
+                share: Uint128::zero(),
+                locked_balance: vec![],
+            },
+        )],
+    )]);
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_ALICE.to_string(),
+        amount: Uint128::from(100_000u128),
+        msg: to_binary(&Cw20HookMsg::StakingTokens {
+            lock_duration: None,
+        })
+        .unwrap(),
+    });
+    let info = mock_info(TEST_ANCHOR_TOKEN, &[]);
+    execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    deps.querier.with_gov_stakers(&[(
+        &TEST_ANCHOR_GOV.to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &StakerResponse {
+                balance: Uint128::from(100_000u128),
+                share: Uint128::from(100_000u128),
+                locked_balance: vec![],
+            },
+        )],
+    )]);
+
+    // nothing is vested right after the fee accrual
+    let msg = ExecuteMsg::WithdrawToken { amount: None };
+    let info = mock_info(TEST_DEV, &[]);
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone());
+    match res {
+        Err(ContractError::SharesLocked {}) => {}
+        _ => panic!("Must return SharesLocked error"),
+    }
+
+    env.block.time = env.block.time.plus_seconds(500);
+    let msg_query = QueryMsg::Vesting {
+        address: TEST_DEV.to_string(),
+    };
+    let res = query(deps.as_ref(), env.clone(), msg_query.clone()).unwrap();
+    let vesting_response: VestingResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        vesting_response,
+        VestingResponse {
+            duration: 1000,
+            vested: Uint128::from(1000u128),
+            unvested: Uint128::from(1000u128),
+        }
+    );
+
+    // only the vested half can be withdrawn
+    execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    let key = deps.api.addr_canonicalize(TEST_DEV).unwrap();
+    let dev_shares = user_states_read(deps.as_ref().storage)
+        .load(key.as_slice())
+        .unwrap();
+    assert_eq!(dev_shares, Uint128::from(1000u128));
+
+    env.block.time = env.block.time.plus_seconds(500);
+    let res = query(deps.as_ref(), env, msg_query).unwrap();
+    let vesting_response: VestingResponse = from_binary(&res).unwrap();
+    assert_eq!(vesting_response.vested, Uint128::from(1000u128));
+    assert_eq!(vesting_response.unvested, Uint128::zero());
+}
+
+#[test]
+fn fails_recover_token_with_stray_anc_credited() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    let msg = ExecuteMsg::RecoverToken {
+        token: TokenInfo::Token {
+            contract_addr: TEST_ANCHOR_TOKEN.to_string(),
+        },
+        amount: Uint128::from(100u128),
+        recipient: TEST_ALICE.to_string(),
+    };
+    let info = mock_info(TEST_ALICE, &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
+    match res {
+        Err(ContractError::Unauthorized {}) => {}
+        _ => panic!("Must return Unauthorized error"),
+    }
+
+    let info = mock_info(TEST_CREATOR, &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::StrayAncCredited {}) => {}
+        _ => panic!("Must return StrayAncCredited error"),
+    }
+}
+
+#[test]
+fn proper_recover_token() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    let info = mock_info(TEST_CREATOR, &[]);
+
+    // native coins and other cw20 are always recoverable
+    let msg = ExecuteMsg::RecoverToken {
+        token: TokenInfo::NativeToken {
+            denom: "uusd".to_string(),
+        },
+        amount: Uint128::from(100u128),
+        recipient: TEST_ALICE.to_string(),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: TEST_ALICE.to_string(),
+            amount: vec![Coin {
+                denom: "uusd".to_string(),
+                amount: Uint128::from(100u128),
+            }],
+        }))]
+    );
+    let msg = ExecuteMsg::RecoverToken {
+        token: TokenInfo::Token {
+            contract_addr: "other_token".to_string(),
+        },
+        amount: Uint128::from(100u128),
+        recipient: TEST_ALICE.to_string(),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: "other_token".to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: TEST_ALICE.to_string(),
+                amount: Uint128::from(100u128),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))]
+    );
+
+    // with refund policy, a deposit only stakes its own amount
+    let msg = ExecuteMsg::UpdateStrayAncPolicy {
+        policy: StrayAncPolicy::Refund,
+    };
+    execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+    deps.querier.with_token_balances(&[(
+        &TEST_ANCHOR_TOKEN.to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(1500u128))],
+    )]);
+    deps.querier.with_gov_stakers(&[(
+        &TEST_ANCHOR_GOV.to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &StakerResponse {
+                balance: Uint128::zero(),
+                share: Uint128::zero(),
+                locked_balance: vec![],
+            },
+        )],
+    )]);
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_BOB.to_string(),
+        amount: Uint128::from(1000u128),
+        msg: to_binary(&Cw20HookMsg::StakingTokens {
+            lock_duration: None,
+        })
+        .unwrap(),
+    });
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(TEST_ANCHOR_TOKEN, &[]),
+        msg,
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: TEST_ANCHOR_TOKEN.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Send {
+                contract: TEST_ANCHOR_GOV.to_string(),
+                amount: Uint128::from(1000u128),
+                msg: to_binary(&GovCw20HookMsg::StakeVotingTokens {}).unwrap(),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))]
+    );
+
+    // after the deposit is staked, only the stray 500 is left
+    deps.querier.with_token_balances(&[(
+        &TEST_ANCHOR_TOKEN.to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(500u128))],
+    )]);
+    let msg = ExecuteMsg::RecoverToken {
+        token: TokenInfo::Token {
+            contract_addr: TEST_ANCHOR_TOKEN.to_string(),
+        },
+        amount: Uint128::from(501u128),
+        recipient: TEST_ALICE.to_string(),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+    match res {
+        Err(ContractError::InsufficientFunds {}) => {}
+        _ => panic!("Must return InsufficientFunds error"),
+    }
+    let msg = ExecuteMsg::RecoverToken {
+        token: TokenInfo::Token {
+            contract_addr: TEST_ANCHOR_TOKEN.to_string(),
+        },
+        amount: Uint128::from(500u128),
+        recipient: TEST_ALICE.to_string(),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: TEST_ANCHOR_TOKEN.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: TEST_ALICE.to_string(),
+                amount: Uint128::from(500u128),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))]
+    );
+}
+
+#[test]
+fn fails_execute_with_unexpected_funds() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    let msg = ExecuteMsg::WithdrawToken { amount: None };
+    let info = mock_info(
+        TEST_ALICE,
+        &[Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(100u128),
+        }],
+    );
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::UnexpectedFunds {}) => {}
+        _ => panic!("Must return UnexpectedFunds error"),
+    }
+}
+
+#[test]
+fn proper_sweep_native() {
+    let balances = vec![
+        Coin {
+            denom: "uluna".to_string(),
+            amount: Uint128::from(10u128),
+        },
+        Coin {
+            denom: "uusd".to_string(),
+            amount: Uint128::from(100u128),
+        },
+    ];
+    let mut deps = mock_dependencies(&balances);
+    mock_instantiate(deps.as_mut());
+    let msg = ExecuteMsg::SweepNative {
+        recipient: TEST_ALICE.to_string(),
+    };
+    let info = mock_info(TEST_ALICE, &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
+    match res {
+        Err(ContractError::Unauthorized {}) => {}
+        _ => panic!("Must return Unauthorized error"),
+    }
+
+    let info = mock_info(TEST_CREATOR, &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone()).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: TEST_ALICE.to_string(),
+            amount: balances,
+        }))]
+    );
+
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    let info = mock_info(TEST_CREATOR, &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::InsufficientFunds {}) => {}
+        _ => panic!("Must return InsufficientFunds error"),
+    }
+}
+
+fn mock_meta_gov(deps: DepsMut, veto_until: u64) {
+    let msg = ExecuteMsg::UpdateMetaGovConfig {
+        quorum: Decimal::percent(50),
+        threshold: Decimal::percent(50),
+        voting_period: 100,
+        veto_until,
+        proposal_min_shares: Decimal::percent(10),
+    };
+    let info = mock_info(TEST_CREATOR, &[]);
+    execute(deps, mock_env(), info, msg).unwrap();
+}
+
+fn mock_shares(deps: DepsMut, alice: u128, bob: u128) {
+    let key = deps.api.addr_canonicalize(TEST_ALICE).unwrap();
+    user_states_store(deps.storage)
+        .save(key.as_slice(), &Uint128::from(alice))
+        .unwrap();
+    let key = deps.api.addr_canonicalize(TEST_BOB).unwrap();
+    user_states_store(deps.storage)
+        .save(key.as_slice(), &Uint128::from(bob))
+        .unwrap();
+    total_shares_store(deps.storage)
+        .save(&Uint128::from(alice + bob))
+        .unwrap();
+}
+
+#[test]
+fn fails_create_proposal_with_meta_gov_disabled() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    mock_shares(deps.as_mut(), 600, 400);
+
+    let msg = ExecuteMsg::CreateProposal {
+        action: ProposalAction::SetPaused { paused: true },
+    };
+    let info = mock_info(TEST_ALICE, &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::MetaGovDisabled {}) => {}
+        _ => panic!("Must return MetaGovDisabled error"),
+    }
+
+    let msg = ExecuteMsg::UpdateMetaGovConfig {
+        quorum: Decimal::percent(50),
+        threshold: Decimal::percent(50),
+        voting_period: 100,
+        veto_until: 0,
+        proposal_min_shares: Decimal::percent(10),
+    };
+    let info = mock_info(TEST_ALICE, &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::Unauthorized {}) => {}
+        _ => panic!("Must return Unauthorized error"),
+    }
+    for (quorum, threshold, proposal_min_shares, voting_period) in [
+        (101u64, 50u64, 10u64, 100u64),
+        (0u64, 50u64, 10u64, 100u64),
+        (50u64, 49u64, 10u64, 100u64),
+        (50u64, 50u64, 0u64, 100u64),
+        (50u64, 50u64, 10u64, 0u64),
+        (50u64, 50u64, 10u64, 30 * 86400 + 1),
+    ]
+    .iter()
+    {
+        let msg = ExecuteMsg::UpdateMetaGovConfig {
+            quorum: Decimal::percent(*quorum),
+            threshold: Decimal::percent(*threshold),
+            voting_period: *voting_period,
+            veto_until: 0,
+            proposal_min_shares: Decimal::percent(*proposal_min_shares),
+        };
+        let info = mock_info(TEST_CREATOR, &[]);
+        let res = execute(deps.as_mut(), mock_env(), info, msg);
+        match res {
+            Err(ContractError::InvalidMetaGovConfig {}) => {}
+            _ => panic!("Must return InvalidMetaGovConfig error"),
+        }
+    }
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::MetaGovConfig {}).unwrap();
+    let config_response: MetaGovConfigResponse = from_binary(&res).unwrap();
+    assert!(!config_response.enabled);
+}
+
+#[test]
+fn proper_meta_gov_proposal() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    mock_meta_gov(deps.as_mut(), 0);
+    mock_shares(deps.as_mut(), 600, 400);
+    let mut env = mock_env();
+
+    // share holders only
+    let msg = ExecuteMsg::CreateProposal {
+        action: ProposalAction::UpdateFeeRate {
+            new_feerate: Decimal::percent(TEST_NEW_FEERATE),
+        },
+    };
+    let info = mock_info(TEST_DEV, &[]);
+    let res = execute(deps.as_mut(), env.clone(), info, msg.clone());
+    match res {
+        Err(ContractError::NothingStaked {}) => {}
+        _ => panic!("Must return NothingStaked error"),
+    }
+    let key = deps.api.addr_canonicalize(TEST_DEV).unwrap();
+    user_states_store(deps.as_mut().storage)
+        .save(key.as_slice(), &Uint128::from(50u128))
+        .unwrap();
+    let info = mock_info(TEST_DEV, &[]);
+    let res = execute(deps.as_mut(), env.clone(), info, msg.clone());
+    match res {
+        Err(ContractError::ProposerSharesTooLow {}) => {}
+        _ => panic!("Must return ProposerSharesTooLow error"),
+    }
+    let info = mock_info(TEST_BOB, &[]);
+    execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::CastVote {
+        proposal_id: 1,
+        vote: ProposalVote::Yes,
+    };
+    let info = mock_info(TEST_ALICE, &[]);
+    execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+    match res {
+        Err(ContractError::AlreadyVoted {}) => {}
+        _ => panic!("Must return AlreadyVoted error"),
+    }
+    let msg = ExecuteMsg::CastVote {
+        proposal_id: 1,
+        vote: ProposalVote::No,
+    };
+    execute(deps.as_mut(), env.clone(), mock_info(TEST_BOB, &[]), msg).unwrap();
+
+    // voted shares are locked while the proposal is in progress
+    deps.querier.with_gov_stakers(&[(
+        &TEST_ANCHOR_GOV.to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &StakerResponse {
+                balance: Uint128::from(1000u128),
+                share: Uint128::from(1000u128),
+                locked_balance: vec![],
+            },
+        )],
+    )]);
+    let msg = ExecuteMsg::WithdrawToken { amount: None };
+    let res = execute(deps.as_mut(), env.clone(), info, msg);
+    match res {
+        Err(ContractError::SharesLocked {}) => {}
+        _ => panic!("Must return SharesLocked error"),
+    }
+
+    let msg = ExecuteMsg::EndProposal { proposal_id: 1 };
+    let info = mock_info(TEST_DEV, &[]);
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone());
+    match res {
+        Err(ContractError::VotingNotEnded {}) => {}
+        _ => panic!("Must return VotingNotEnded error"),
+    }
+
+    env.block.time = env.block.time.plus_seconds(100);
+    let vote_msg = ExecuteMsg::CastVote {
+        proposal_id: 1,
+        vote: ProposalVote::No,
+    };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), vote_msg);
+    match res {
+        Err(ContractError::VotingClosed {}) => {}
+        _ => panic!("Must return VotingClosed error"),
+    }
+    execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let feerate = feerate_read(deps.as_ref().storage).load().unwrap();
+    assert_eq!(feerate, Decimal::percent(TEST_NEW_FEERATE));
+    let res = query(deps.as_ref(), env, QueryMsg::Proposal { proposal_id: 1 }).unwrap();
+    let proposal_response: ProposalResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        proposal_response,
+        ProposalResponse {
+            id: 1,
+            proposer: TEST_BOB.to_string(),
+            action: ProposalAction::UpdateFeeRate {
+                new_feerate: Decimal::percent(TEST_NEW_FEERATE),
+            },
+            yes_votes: Uint128::from(600u128),
+            no_votes: Uint128::from(400u128),
+            end_time: mock_env().block.time.seconds() + 100,
+            status: ProposalStatus::Passed,
+        }
+    );
+}
+
+#[test]
+fn proper_meta_gov_vote_with_boosted_weight() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    mock_meta_gov(deps.as_mut(), 0);
+    mock_shares(deps.as_mut(), 600, 400);
+    let mut env = mock_env();
+
+    // bob locked his shares, an expired position adds nothing
+    let bob = deps.api.addr_canonicalize(TEST_BOB).unwrap();
+    for (id, unlock_time) in [(1u64, env.block.time.seconds() + 1000), (2u64, 0u64)].iter() {
+        positions_store(deps.as_mut().storage, &bob)
+            .save(
+                &id.to_be_bytes(),
+                &Position {
+                    id: *id,
+                    shares: Uint128::from(200u128),
+                    weight: Uint128::from(400u128),
+                    unlock_time: *unlock_time,
+                },
+            )
+            .unwrap();
+    }
+
+    let msg = ExecuteMsg::CreateProposal {
+        action: ProposalAction::SetPaused { paused: true },
+    };
+    execute(deps.as_mut(), env.clone(), mock_info(TEST_ALICE, &[]), msg).unwrap();
+    let msg = ExecuteMsg::CastVote {
+        proposal_id: 1,
+        vote: ProposalVote::Yes,
+    };
+    execute(deps.as_mut(), env.clone(), mock_info(TEST_ALICE, &[]), msg).unwrap();
+    let msg = ExecuteMsg::CastVote {
+        proposal_id: 1,
+        vote: ProposalVote::No,
+    };
+    let res = execute(deps.as_mut(), env.clone(), mock_info(TEST_BOB, &[]), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("method", "cast_vote"),
+            attr("proposal_id", "1"),
+            attr("vote", "No"),
+            attr("shares", "400"),
+            attr("weight", "600"),
+        ]
+    );
+
+    env.block.time = env.block.time.plus_seconds(100);
+    let msg = ExecuteMsg::EndProposal { proposal_id: 1 };
+    execute(deps.as_mut(), env.clone(), mock_info(TEST_DEV, &[]), msg).unwrap();
+    let res = query(deps.as_ref(), env, QueryMsg::Proposal { proposal_id: 1 }).unwrap();
+    let proposal_response: ProposalResponse = from_binary(&res).unwrap();
+    assert_eq!(proposal_response.yes_votes, Uint128::from(600u128));
+    assert_eq!(proposal_response.no_votes, Uint128::from(600u128));
+    assert_eq!(proposal_response.status, ProposalStatus::Rejected);
+}
+
+#[test]
+fn proper_meta_gov_proposal_rejected_and_vetoed() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    let veto_until = mock_env().block.time.seconds() + 1000;
+    mock_meta_gov(deps.as_mut(), veto_until);
+    mock_shares(deps.as_mut(), 400, 600);
+    let mut env = mock_env();
+
+    // below quorum
+    let msg = ExecuteMsg::CreateProposal {
+        action: ProposalAction::SetPaused { paused: true },
+    };
+    let info = mock_info(TEST_ALICE, &[]);
+    execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
+    let vote_msg = ExecuteMsg::CastVote {
+        proposal_id: 1,
+        vote: ProposalVote::Yes,
+    };
+    execute(deps.as_mut(), env.clone(), info.clone(), vote_msg).unwrap();
+
+    // only the owner vetoes
+    execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
+    let veto_msg = ExecuteMsg::VetoProposal { proposal_id: 2 };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), veto_msg.clone());
+    match res {
+        Err(ContractError::Unauthorized {}) => {}
+        _ => panic!("Must return Unauthorized error"),
+    }
+    let owner_info = mock_info(TEST_CREATOR, &[]);
+    execute(deps.as_mut(), env.clone(), owner_info.clone(), veto_msg).unwrap();
+
+    env.block.time = env.block.time.plus_seconds(100);
+    let end_msg = ExecuteMsg::EndProposal { proposal_id: 1 };
+    execute(deps.as_mut(), env.clone(), info.clone(), end_msg).unwrap();
+    assert!(!paused_read(deps.as_ref().storage).load().unwrap());
+    let end_msg = ExecuteMsg::EndProposal { proposal_id: 2 };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), end_msg);
+    match res {
+        Err(ContractError::VotingClosed {}) => {}
+        _ => panic!("Must return VotingClosed error"),
+    }
+
+    let msg_query = QueryMsg::Proposals {
+        start_after: None,
+        limit: None,
+    };
+    let res = query(deps.as_ref(), env.clone(), msg_query).unwrap();
+    let proposals_response: ProposalsResponse = from_binary(&res).unwrap();
+    let status: Vec<ProposalStatus> = proposals_response
+        .proposals
+        .into_iter()
+        .map(|x| x.status)
+        .collect();
+    assert_eq!(
+        status,
+        vec![ProposalStatus::Rejected, ProposalStatus::Vetoed]
+    );
+
+    // the veto ends with the transition period
+    env.block.time = env.block.time.plus_seconds(900);
+    execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    let veto_msg = ExecuteMsg::VetoProposal { proposal_id: 3 };
+    let res = execute(deps.as_mut(), env, owner_info, veto_msg);
+    match res {
+        Err(ContractError::VetoExpired {}) => {}
+        _ => panic!("Must return VetoExpired error"),
+    }
+}
+
+#[test]
+fn proper_create_gov_poll() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    let poll_msg = to_binary(&Cw20HookMsg::CreateGovPoll {
+        title: "title".to_string(),
+        description: "description".to_string(),
+        link: None,
+        execute_msgs: None,
+    })
+    .unwrap();
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_ALICE.to_string(),
+        amount: Uint128::from(100u128),
+        msg: poll_msg.clone(),
+    });
+    let info = mock_info(TEST_ANCHOR_TOKEN, &[]);
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+    match res {
+        Err(ContractError::Unauthorized {}) => {}
+        _ => panic!("Must return Unauthorized error"),
+    }
+
+    // the deposit sent by owner goes to gov with the poll, the pool is not touched
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_CREATOR.to_string(),
+        amount: Uint128::from(100u128),
+        msg: poll_msg,
+    });
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::reply_on_success(
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: TEST_ANCHOR_TOKEN.to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Send {
+                    contract: TEST_ANCHOR_GOV.to_string(),
+                    amount: Uint128::from(100u128),
+                    msg: to_binary(&GovCw20HookMsg::CreatePoll {
+                        title: "title".to_string(),
+                        description: "description".to_string(),
+                        link: None,
+                        execute_msgs: None,
+                    })
+                    .unwrap(),
+                })
+                .unwrap(),
+                funds: vec![],
+            }),
+            CREATE_POLL_REPLY_ID
+        )]
+    );
+
+    let reply_msg = Reply {
+        id: CREATE_POLL_REPLY_ID,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: None,
+        }),
+    };
+    let res = reply(deps.as_mut(), mock_env(), reply_msg);
+    match res {
+        Err(ContractError::PollIdNotFound {}) => {}
+        _ => panic!("Must return PollIdNotFound error"),
+    }
+    let reply_msg = Reply {
+        id: CREATE_POLL_REPLY_ID,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![Event::new("wasm")
+                .add_attribute("action", "create_poll")
+                .add_attribute("poll_id", "7")],
+            data: None,
+        }),
+    };
+    reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    let msg = QueryMsg::GovPolls {
+        start_after: None,
+        limit: None,
+    };
+    let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+    let polls_response: GovPollsResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        polls_response.polls,
+        vec![GovPollResponse {
+            poll_id: 7,
+            deposit: Uint128::from(100u128),
+            settled: false,
+            funder: Some(TEST_CREATOR.to_string()),
+        }]
+    );
+}
+
+#[test]
+fn proper_settle_gov_poll() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    mock_shares(deps.as_mut(), 600, 400);
+    deps.querier.with_token_balances(&[(
+        &TEST_ANCHOR_TOKEN.to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(150u128))],
+    )]);
+    deps.querier.with_gov_stakers(&[(
+        &TEST_ANCHOR_GOV.to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &StakerResponse {
+                balance: Uint128::from(1000u128),
+                share: Uint128::from(1000u128),
+                locked_balance: vec![],
+            },
+        )],
+    )]);
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_CREATOR.to_string(),
+        amount: Uint128::from(100u128),
+        msg: to_binary(&Cw20HookMsg::CreateGovPoll {
+            title: "title".to_string(),
+            description: "description".to_string(),
+            link: None,
+            execute_msgs: None,
+        })
+        .unwrap(),
+    });
+    let info = mock_info(TEST_ANCHOR_TOKEN, &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let reply_msg = Reply {
+        id: CREATE_POLL_REPLY_ID,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![Event::new("wasm")
+                .add_attribute("action", "create_poll")
+                .add_attribute("poll_id", "7")],
+            data: None,
+        }),
+    };
+    reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    // the returned deposit is not stray, even under refund
+    let info = mock_info(TEST_CREATOR, &[]);
+    let msg = ExecuteMsg::UpdateStrayAncPolicy {
+        policy: StrayAncPolicy::Refund,
+    };
+    execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+    let msg = ExecuteMsg::RecoverToken {
+        token: TokenInfo::Token {
+            contract_addr: TEST_ANCHOR_TOKEN.to_string(),
+        },
+        amount: Uint128::from(51u128),
+        recipient: TEST_CREATOR.to_string(),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::InsufficientFunds {}) => {}
+        _ => panic!("Must return InsufficientFunds error"),
+    }
+
+    let info = mock_info(TEST_BOB, &[]);
+    let msg = ExecuteMsg::SettleGovPoll { poll_id: 7 };
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone());
+    match res {
+        Err(ContractError::PollInProgress {}) => {}
+        _ => panic!("Must return PollInProgress error"),
+    }
+
+    // the deposit paid by owner goes back to owner, not into the pool
+    deps.querier.with_poll_status(7, PollStatus::Passed);
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: TEST_ANCHOR_TOKEN.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: TEST_CREATOR.to_string(),
+                amount: Uint128::from(100u128),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))]
+    );
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("method", "settle_gov_poll"),
+            attr("poll_id", "7"),
+            attr("returned", "100"),
+        ]
+    );
+
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+    match res {
+        Err(ContractError::PollSettled {}) => {}
+        _ => panic!("Must return PollSettled error"),
+    }
+
+    // a rejected poll below quorum keeps its deposit, the stray ANC stays for refund
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_CREATOR.to_string(),
+        amount: Uint128::from(100u128),
+        msg: to_binary(&Cw20HookMsg::CreateGovPoll {
+            title: "title".to_string(),
+            description: "description".to_string(),
+            link: None,
+            execute_msgs: None,
+        })
+        .unwrap(),
+    });
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(TEST_ANCHOR_TOKEN, &[]),
+        msg,
+    )
+    .unwrap();
+    let reply_msg = Reply {
+        id: CREATE_POLL_REPLY_ID,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![Event::new("wasm")
+                .add_attribute("action", "create_poll")
+                .add_attribute("poll_id", "8")],
+            data: None,
+        }),
+    };
+    reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+    deps.querier.with_poll_status(8, PollStatus::Rejected);
+    deps.querier
+        .with_poll_tally(8, Uint128::from(50u128), Uint128::from(1000u128));
+    let msg = ExecuteMsg::SettleGovPoll { poll_id: 8 };
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert!(res.messages.is_empty());
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("method", "settle_gov_poll"),
+            attr("poll_id", "8"),
+            attr("returned", "0"),
+        ]
+    );
+
+    let msg = QueryMsg::GovPolls {
+        start_after: None,
+        limit: None,
+    };
+    let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+    let polls_response: GovPollsResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        polls_response.polls,
+        vec![
+            GovPollResponse {
+                poll_id: 7,
+                deposit: Uint128::from(100u128),
+                settled: true,
+                funder: Some(TEST_CREATOR.to_string()),
+            },
+            GovPollResponse {
+                poll_id: 8,
+                deposit: Uint128::from(100u128),
+                settled: true,
+                funder: Some(TEST_CREATOR.to_string()),
+            },
+        ]
+    );
+}
+
+#[test]
+fn proper_settle_gov_poll_with_meta_gov() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    mock_meta_gov(deps.as_mut(), 0);
+    mock_shares(deps.as_mut(), 600, 400);
+    let mut env = mock_env();
+
+    let msg = ExecuteMsg::CreateProposal {
+        action: ProposalAction::CreateGovPoll {
+            title: "title".to_string(),
+            description: "description".to_string(),
+            link: None,
+            execute_msgs: None,
+            deposit: Uint128::from(100u128),
+        },
+    };
+    let info = mock_info(TEST_ALICE, &[]);
+    execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    let msg = ExecuteMsg::CastVote {
+        proposal_id: 1,
+        vote: ProposalVote::Yes,
+    };
+    execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    env.block.time = env.block.time.plus_seconds(100);
+    deps.querier.with_gov_stakers(&[(
+        &TEST_ANCHOR_GOV.to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &StakerResponse {
+                balance: Uint128::from(1000u128),
+                share: Uint128::from(1000u128),
+                locked_balance: vec![],
+            },
+        )],
+    )]);
+    let msg = ExecuteMsg::EndProposal { proposal_id: 1 };
+    execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    let reply_msg = Reply {
+        id: CREATE_POLL_WITHDRAW_REPLY_ID,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: None,
+        }),
+    };
+    reply(deps.as_mut(), env.clone(), reply_msg).unwrap();
+    let reply_msg = Reply {
+        id: CREATE_POLL_REPLY_ID,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![Event::new("wasm")
+                .add_attribute("action", "create_poll")
+                .add_attribute("poll_id", "9")],
+            data: None,
+        }),
+    };
+    reply(deps.as_mut(), env.clone(), reply_msg).unwrap();
+
+    // rejected above quorum, gov refunded the deposit paid by the pool
+    deps.querier.with_poll_status(9, PollStatus::Rejected);
+    deps.querier
+        .with_poll_tally(9, Uint128::from(200u128), Uint128::from(1000u128));
+    let msg = ExecuteMsg::SettleGovPoll { poll_id: 9 };
+    deps.querier.with_token_balances(&[(
+        &TEST_ANCHOR_TOKEN.to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(99u128))],
+    )]);
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone());
+    match res {
+        Err(ContractError::InsufficientFunds {}) => {}
+        _ => panic!("Must return InsufficientFunds error"),
+    }
+    deps.querier.with_token_balances(&[(
+        &TEST_ANCHOR_TOKEN.to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(100u128))],
+    )]);
+    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: TEST_ANCHOR_TOKEN.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Send {
+                contract: TEST_ANCHOR_GOV.to_string(),
+                amount: Uint128::from(100u128),
+                msg: to_binary(&GovCw20HookMsg::StakeVotingTokens {}).unwrap(),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))]
+    );
+
+    let msg = QueryMsg::GovPolls {
+        start_after: None,
+        limit: None,
+    };
+    let res = query(deps.as_ref(), env, msg).unwrap();
+    let polls_response: GovPollsResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        polls_response.polls,
+        vec![GovPollResponse {
+            poll_id: 9,
+            deposit: Uint128::from(100u128),
+            settled: true,
+            funder: None,
+        }]
+    );
+}
+
+#[test]
+fn proper_stake_with_outstanding_poll_deposit() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    let msg = ExecuteMsg::UpdateFeeRate {
+        new_feerate: Decimal::zero(),
+    };
+    let info = mock_info(TEST_CREATOR, &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    mock_meta_gov(deps.as_mut(), 0);
+    mock_shares(deps.as_mut(), 600, 400);
+    let mut env = mock_env();
+
+    let msg = ExecuteMsg::CreateProposal {
+        action: ProposalAction::CreateGovPoll {
+            title: "title".to_string(),
+            description: "description".to_string(),
+            link: None,
+            execute_msgs: None,
+            deposit: Uint128::from(100u128),
+        },
+    };
+    let info = mock_info(TEST_ALICE, &[]);
+    execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    let msg = ExecuteMsg::CastVote {
+        proposal_id: 1,
+        vote: ProposalVote::Yes,
+    };
+    execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    env.block.time = env.block.time.plus_seconds(100);
+    deps.querier.with_gov_stakers(&[(
+        &TEST_ANCHOR_GOV.to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &StakerResponse {
+                balance: Uint128::from(1000u128),
+                share: Uint128::from(1000u128),
+                locked_balance: vec![],
+            },
+        )],
+    )]);
+    let msg = ExecuteMsg::EndProposal { proposal_id: 1 };
+    execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+    let reply_msg = Reply {
+        id: CREATE_POLL_WITHDRAW_REPLY_ID,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: None,
+        }),
+    };
+    reply(deps.as_mut(), env.clone(), reply_msg).unwrap();
+    let reply_msg = Reply {
+        id: CREATE_POLL_REPLY_ID,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![Event::new("wasm")
+                .add_attribute("action", "create_poll")
+                .add_attribute("poll_id", "9")],
+            data: None,
+        }),
+    };
+    reply(deps.as_mut(), env.clone(), reply_msg).unwrap();
+
+    // the deposit left gov for the poll, the shares are still priced with it
+    deps.querier.with_gov_stakers(&[(
+        &TEST_ANCHOR_GOV.to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &StakerResponse {
+                balance: Uint128::from(900u128),
+                share: Uint128::from(900u128),
+                locked_balance: vec![],
+            },
+        )],
+    )]);
+    deps.querier.with_token_balances(&[(
+        &TEST_ANCHOR_TOKEN.to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(100u128))],
+    )]);
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_BOB.to_string(),
+        amount: Uint128::from(100u128),
+        msg: to_binary(&Cw20HookMsg::StakingTokens {
+            lock_duration: None,
+        })
+        .unwrap(),
+    });
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_ANCHOR_TOKEN, &[]),
+        msg,
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages[0],
+        SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: TEST_ANCHOR_TOKEN.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Send {
+                contract: TEST_ANCHOR_GOV.to_string(),
+                amount: Uint128::from(100u128),
+                msg: to_binary(&GovCw20HookMsg::StakeVotingTokens {}).unwrap(),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))
+    );
+    let msg = QueryMsg::UserState {
+        user: TEST_BOB.to_string(),
+    };
+    let res = query(deps.as_ref(), env.clone(), msg.clone()).unwrap();
+    let user_state_response: UserStateResponse = from_binary(&res).unwrap();
+    assert_eq!(user_state_response.shares, Uint128::from(500u128));
+
+    deps.querier.with_gov_stakers(&[(
+        &TEST_ANCHOR_GOV.to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &StakerResponse {
+                balance: Uint128::from(1000u128),
+                share: Uint128::from(1000u128),
+                locked_balance: vec![],
+            },
+        )],
+    )]);
+    // 1000 in gov and 100 in the poll back 1100 shares
+    let msg_withdraw = ExecuteMsg::WithdrawToken {
+        amount: Some(Uint128::from(110u128)),
+    };
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_BOB, &[]),
+        msg_withdraw,
+    )
+    .unwrap();
+    let res = query(deps.as_ref(), env, msg).unwrap();
+    let user_state_response: UserStateResponse = from_binary(&res).unwrap();
+    assert_eq!(user_state_response.shares, Uint128::from(390u128));
+}
+
+#[test]
+fn proper_create_gov_poll_with_meta_gov() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    mock_meta_gov(deps.as_mut(), 0);
+    mock_shares(deps.as_mut(), 600, 400);
+    let mut env = mock_env();
+
+    let msg = ExecuteMsg::CreateProposal {
+        action: ProposalAction::CreateGovPoll {
+            title: "title".to_string(),
+            description: "description".to_string(),
+            link: None,
+            execute_msgs: None,
+            deposit: Uint128::from(100u128),
+        },
+    };
+    let info = mock_info(TEST_ALICE, &[]);
+    // the deposit comes from the pool, at most the gov proposal deposit
+    deps.querier.with_proposal_deposit(Uint128::from(99u128));
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone());
+    match res {
+        Err(ContractError::DepositExceedsProposalDeposit {}) => {}
+        _ => panic!("Must return DepositExceedsProposalDeposit error"),
+    }
+    deps.querier.with_proposal_deposit(Uint128::from(100u128));
+    execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    let msg = ExecuteMsg::CastVote {
+        proposal_id: 1,
+        vote: ProposalVote::Yes,
+    };
+    execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    env.block.time = env.block.time.plus_seconds(100);
+    deps.querier.with_gov_stakers(&[(
+        &TEST_ANCHOR_GOV.to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &StakerResponse {
+                balance: Uint128::from(1000u128),
+                share: Uint128::from(1000u128),
+                locked_balance: vec![],
+            },
+        )],
+    )]);
+    let msg = ExecuteMsg::EndProposal { proposal_id: 1 };
+    let res = execute(deps.as_mut(), env, info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::reply_on_success(
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: TEST_ANCHOR_GOV.to_string(),
+                msg: to_binary(&GovExcuteMsg::WithdrawVotingTokens {
+                    amount: Some(Uint128::from(100u128))
+                })
+                .unwrap(),
+                funds: vec![],
+            }),
+            CREATE_POLL_WITHDRAW_REPLY_ID
+        )]
+    );
+}
+
+#[test]
+fn proper_end_proposal_with_invalid_action() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    mock_meta_gov(deps.as_mut(), 0);
+    mock_shares(deps.as_mut(), 600, 400);
+    let mut env = mock_env();
+
+    let msg = ExecuteMsg::CreateProposal {
+        action: ProposalAction::CreateGovPoll {
+            title: "title".to_string(),
+            description: "description".to_string(),
+            link: None,
+            execute_msgs: None,
+            deposit: Uint128::from(100u128),
+        },
+    };
+    let info = mock_info(TEST_ALICE, &[]);
+    execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    let msg = ExecuteMsg::CastVote {
+        proposal_id: 1,
+        vote: ProposalVote::Yes,
+    };
+    execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+    // gov lowered its deposit during the vote, the proposal fails instead of staying open
+    deps.querier.with_proposal_deposit(Uint128::from(50u128));
+    env.block.time = env.block.time.plus_seconds(100);
+    let msg = ExecuteMsg::EndProposal { proposal_id: 1 };
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
+    assert!(res.messages.is_empty());
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("method", "end_proposal"),
+            attr("proposal_id", "1"),
+            attr("status", "Failed"),
+            attr(
+                "error",
+                ContractError::DepositExceedsProposalDeposit {}.to_string()
+            ),
+        ]
+    );
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::Proposal { proposal_id: 1 },
+    )
+    .unwrap();
+    let proposal_response: ProposalResponse = from_binary(&res).unwrap();
+    assert_eq!(proposal_response.status, ProposalStatus::Failed);
+
+    let res = execute(deps.as_mut(), env, info, msg);
+    match res {
+        Err(ContractError::VotingClosed {}) => {}
+        _ => panic!("Must return VotingClosed error"),
+    }
+}
+
+#[test]
+fn query_health() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    let msg = ExecuteMsg::UpdateFeeRate {
+        new_feerate: Decimal::zero(),
+    };
+    let info = mock_info(TEST_CREATOR, &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    deps.querier.with_token_balances(&[(
+        &TEST_ANCHOR_TOKEN.to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(1000u128))],
+    )]);
+    deps.querier.with_gov_stakers(&[(
+        &TEST_ANCHOR_GOV.to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &StakerResponse {
+                balance: Uint128::zero(),
+                share: Uint128::zero(),
+                locked_balance: vec![],
+            },
+        )],
+    )]);
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_ALICE.to_string(),
+        amount: Uint128::from(1000u128),
+        msg: to_binary(&Cw20HookMsg::StakingTokens {
+            lock_duration: None,
+        })
+        .unwrap(),
+    });
+    let info = mock_info(TEST_ANCHOR_TOKEN, &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    // rewards deposited into gov
+    deps.querier.with_gov_stakers(&[(
+        &TEST_ANCHOR_GOV.to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &StakerResponse {
+                balance: Uint128::from(1100u128),
+                share: Uint128::from(1000u128),
+                locked_balance: vec![],
+            },
+        )],
+    )]);
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Health {}).unwrap();
+    let health_response: HealthResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        health_response,
+        HealthResponse {
+            gov_balance: Uint128::from(1100u128),
+            gov_share: Uint128::from(1000u128),
+            total_shares: Uint128::from(1000u128),
+            checkpoint_balance: Uint128::from(1000u128),
+            checkpoint_share: Uint128::from(1000u128),
+            share_discrepancy: Uint128::zero(),
+            share_tolerance: Uint128::from(1000u128),
+            total_shares_discrepancy: Uint128::zero(),
+            reward_growth: Uint128::from(100u128),
+            loss: Uint128::zero(),
+            balance_per_share: Decimal::from_ratio(11u128, 10u128),
+            healthy: true,
+        }
+    );
+
+    // slashed and diverged from the vault view
+    deps.querier.with_gov_stakers(&[(
+        &TEST_ANCHOR_GOV.to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &StakerResponse {
+                balance: Uint128::from(900u128),
+                share: Uint128::from(1200u128),
+                locked_balance: vec![],
+            },
+        )],
+    )]);
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Health {}).unwrap();
+    let health_response: HealthResponse = from_binary(&res).unwrap();
+    assert_eq!(health_response.share_discrepancy, Uint128::from(200u128));
+    assert_eq!(health_response.loss, Uint128::from(250u128));
+    assert_eq!(health_response.reward_growth, Uint128::zero());
+    assert!(!health_response.healthy);
+
+    // gov rounds the new share at its own totals, one share off is within tolerance
+    deps.querier.with_token_balances(&[(
+        &TEST_ANCHOR_TOKEN.to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(100u128))],
+    )]);
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_ALICE.to_string(),
+        amount: Uint128::from(100u128),
+        msg: to_binary(&Cw20HookMsg::StakingTokens {
+            lock_duration: None,
+        })
+        .unwrap(),
+    });
+    let info = mock_info(TEST_ANCHOR_TOKEN, &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    deps.querier.with_gov_stakers(&[(
+        &TEST_ANCHOR_GOV.to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &StakerResponse {
+                balance: Uint128::from(1000u128),
+                share: Uint128::from(1332u128),
+                locked_balance: vec![],
+            },
+        )],
+    )]);
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Health {}).unwrap();
+    let health_response: HealthResponse = from_binary(&res).unwrap();
+    assert_eq!(health_response.checkpoint_share, Uint128::from(1333u128));
+    assert_eq!(health_response.share_discrepancy, Uint128::from(1u128));
+    assert_eq!(health_response.share_tolerance, Uint128::from(1u128));
+    assert_eq!(health_response.loss, Uint128::zero());
+    assert!(health_response.healthy);
+}
+
+#[test]
+fn query_invariants() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    mock_shares(deps.as_mut(), 600, 400);
+    let key = deps.api.addr_canonicalize(TEST_DEV).unwrap();
+    user_states_store(deps.as_mut().storage)
+        .save(key.as_slice(), &Uint128::zero())
+        .unwrap();
+    deps.querier.with_gov_stakers(&[(
+        &TEST_ANCHOR_GOV.to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &StakerResponse {
+                balance: Uint128::from(1000u128),
+                share: Uint128::from(1000u128),
+                locked_balance: vec![],
+            },
+        )],
+    )]);
+
+    let msg = QueryMsg::Invariants {
+        start_after: None,
+        limit: Some(2),
+        carry: None,
+    };
+    let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+    let first_page: InvariantsResponse = from_binary(&res).unwrap();
+    assert!(!first_page.finished);
+    assert!(!first_page.shares_match);
+    assert!(first_page.balance_covers_claims);
+
+    let msg = QueryMsg::Invariants {
+        start_after: first_page.last_user,
+        limit: Some(2),
+        carry: Some(first_page.carry),
+    };
+    let res = query(deps.as_ref(), mock_env(), msg.clone()).unwrap();
+    let last_page: InvariantsResponse = from_binary(&res).unwrap();
+    assert!(last_page.finished);
+    assert!(last_page.shares_match);
+    assert!(last_page.balance_covers_claims);
+    assert_eq!(last_page.carry.shares, Uint128::from(1000u128));
+    assert_eq!(last_page.carry.claims, Uint128::from(1000u128));
+    let mut zero_share_users = first_page.zero_share_users;
+    zero_share_users.extend(last_page.zero_share_users);
+    assert_eq!(zero_share_users, vec![TEST_DEV.to_string()]);
+
+    // accounting bug, shares do not add up
+    total_shares_store(deps.as_mut().storage)
+        .save(&Uint128::from(900u128))
+        .unwrap();
+    let msg = QueryMsg::Invariants {
+        start_after: None,
+        limit: None,
+        carry: None,
+    };
+    let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+    let invariants_response: InvariantsResponse = from_binary(&res).unwrap();
+    assert!(invariants_response.finished);
+    assert!(!invariants_response.shares_match);
+    assert!(!invariants_response.balance_covers_claims);
+}
+
+fn mock_mirror_instantiate(deps: DepsMut) {
+    let msg = InstantiateMsg {
+        feerate: Decimal::zero(),
+        dev: TEST_DEV.to_string(),
+        anchor_token: TEST_ANCHOR_TOKEN.to_string(),
+        anchor_gov: TEST_ANCHOR_GOV.to_string(),
+        gov_kind: Some(GovKind::Mirror),
+        owner: None,
+    };
+    let info = mock_info(TEST_CREATOR, &[]);
+    instantiate(deps, mock_env(), info, msg).unwrap();
+}
+
+#[test]
+fn proper_mirror_gov() {
+    let mut deps = mock_dependencies(&[]);
+    mock_mirror_instantiate(deps.as_mut());
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config_response: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(config_response.gov_kind, GovKind::Mirror);
+
+    // deposit is staked with the mirror hook
+    let amount = Uint128::from(1000u128);
+    deps.querier.with_token_balances(&[(
+        &TEST_ANCHOR_TOKEN.to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &amount)],
+    )]);
+    deps.querier.with_mirror_gov_stakers(&[(
+        &TEST_ANCHOR_GOV.to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &MirrorStakerResponse {
+                balance: Uint128::zero(),
+                share: Uint128::zero(),
+                locked_balance: vec![],
+                pending_voting_rewards: Uint128::zero(),
+                withdrawable_polls: vec![],
+            },
+        )],
+    )]);
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_ALICE.to_string(),
+        amount,
+        msg: to_binary(&Cw20HookMsg::StakingTokens {
+            lock_duration: None,
+        })
+        .unwrap(),
+    });
+    let info = mock_info(TEST_ANCHOR_TOKEN, &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: TEST_ANCHOR_TOKEN.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Send {
+                contract: TEST_ANCHOR_GOV.to_string(),
+                amount,
+                msg: to_binary(&mirror::Cw20HookMsg::StakeVotingTokens {}).unwrap(),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))]
+    );
+
+    // the mirror staker response carries abstain votes and voting rewards
+    deps.querier.with_mirror_gov_stakers(&[(
+        &TEST_ANCHOR_GOV.to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &MirrorStakerResponse {
+                balance: Uint128::from(1200u128),
+                share: Uint128::from(1000u128),
+                locked_balance: vec![(
+                    1,
+                    MirrorVoterInfo {
+                        vote: MirrorVoteOption::Abstain,
+                        balance: Uint128::from(200u128),
+                    },
+                )],
+                pending_voting_rewards: Uint128::from(10u128),
+                withdrawable_polls: vec![(1, Uint128::from(10u128))],
+            },
+        )],
+    )]);
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::State {}).unwrap();
+    let state_response: StateResponse = from_binary(&res).unwrap();
+    assert_eq!(state_response.available_balance, Uint128::from(1000u128));
+    assert_eq!(state_response.locked_balance, Uint128::from(200u128));
+
+    // withdraw is sent with the mirror message
+    let msg = ExecuteMsg::WithdrawToken {
+        amount: Some(Uint128::from(600u128)),
+    };
+    let info = mock_info(TEST_ALICE, &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::reply_on_success(
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: TEST_ANCHOR_GOV.to_string(),
+                msg: to_binary(&mirror::ExecuteMsg::WithdrawVotingTokens {
+                    amount: Some(Uint128::from(600u128))
+                })
+                .unwrap(),
+                funds: vec![],
+            }),
+            WITHDRAW_REPLY_ID
+        )]
+    );
+}
+
+#[test]
+fn proper_create_gov_poll_with_mirror_gov() {
+    let mut deps = mock_dependencies(&[]);
+    mock_mirror_instantiate(deps.as_mut());
+    deps.querier.with_mirror_gov_stakers(&[(
+        &TEST_ANCHOR_GOV.to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &MirrorStakerResponse {
+                balance: Uint128::from(1000u128),
+                share: Uint128::from(1000u128),
+                locked_balance: vec![],
+                pending_voting_rewards: Uint128::zero(),
+                withdrawable_polls: vec![],
+            },
+        )],
+    )]);
+    let execute_msg = PollExecuteMsg {
+        order: 1,
+        contract: TEST_DEV.to_string(),
+        msg: to_binary(&ExecuteMsg::ClaimFees {}).unwrap(),
+    };
+
+    // mirror polls take a single execute msg
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_CREATOR.to_string(),
+        amount: Uint128::from(100u128),
+        msg: to_binary(&Cw20HookMsg::CreateGovPoll {
+            title: "title".to_string(),
+            description: "description".to_string(),
+            link: None,
+            execute_msgs: Some(vec![execute_msg.clone(), execute_msg.clone()]),
+        })
+        .unwrap(),
+    });
+    let info = mock_info(TEST_ANCHOR_TOKEN, &[]);
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+    match res {
+        Err(ContractError::Std(StdError::GenericErr { .. })) => {}
+        _ => panic!("Must return StdError::GenericErr"),
+    }
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_CREATOR.to_string(),
+        amount: Uint128::from(100u128),
+        msg: to_binary(&Cw20HookMsg::CreateGovPoll {
+            title: "title".to_string(),
+            description: "description".to_string(),
+            link: None,
+            execute_msgs: Some(vec![execute_msg.clone()]),
+        })
+        .unwrap(),
+    });
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::reply_on_success(
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: TEST_ANCHOR_TOKEN.to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Send {
+                    contract: TEST_ANCHOR_GOV.to_string(),
+                    amount: Uint128::from(100u128),
+                    msg: to_binary(&mirror::Cw20HookMsg::CreatePoll {
+                        title: "title".to_string(),
+                        description: "description".to_string(),
+                        link: None,
+                        execute_msg: Some(mirror::PollExecuteMsg {
+                            contract: execute_msg.contract,
+                            msg: execute_msg.msg,
+                        }),
+                    })
+                    .unwrap(),
+                })
+                .unwrap(),
+                funds: vec![],
+            }),
+            CREATE_POLL_REPLY_ID
+        )]
+    );
+}
+
+#[test]
+fn proper_update_hooks() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    let msg = ExecuteMsg::UpdateHooks {
+        add: vec![HookInfo {
+            contract: TEST_HOOK.to_string(),
+            fatal: true,
+        }],
+        remove: vec![],
+    };
+    let info = mock_info(TEST_ALICE, &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg.clone());
+    match res {
+        Err(ContractError::Unauthorized {}) => {}
+        _ => panic!("Must return Unauthorized error"),
+    }
+    let info = mock_info(TEST_CREATOR, &[]);
+    execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+    // adding it again updates fatal
+    let msg = ExecuteMsg::UpdateHooks {
+        add: vec![
+            HookInfo {
+                contract: TEST_HOOK.to_string(),
+                fatal: false,
+            },
+            HookInfo {
+                contract: TEST_HOOK2.to_string(),
+                fatal: true,
+            },
+        ],
+        remove: vec![],
+    };
+    execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Hooks {}).unwrap();
+    let hooks_response: HooksResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        hooks_response.hooks,
+        vec![
+            HookInfo {
+                contract: TEST_HOOK.to_string(),
+                fatal: false,
+            },
+            HookInfo {
+                contract: TEST_HOOK2.to_string(),
+                fatal: true,
+            },
+        ]
+    );
+
+    let msg = ExecuteMsg::UpdateHooks {
+        add: vec![],
+        remove: vec![TEST_HOOK.to_string()],
+    };
+    execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Hooks {}).unwrap();
+    let hooks_response: HooksResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        hooks_response.hooks,
+        vec![HookInfo {
+            contract: TEST_HOOK2.to_string(),
+            fatal: true,
+        }]
+    );
+
+    let msg = ExecuteMsg::UpdateHooks {
+        add: (0..10)
+            .map(|i| HookInfo {
+                contract: format!("extra_hook{}", i),
+                fatal: false,
+            })
+            .collect(),
+        remove: vec![],
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+    match res {
+        Err(ContractError::TooManyHooks {}) => {}
+        _ => panic!("Must return TooManyHooks error"),
+    }
+}
+
+#[test]
+fn proper_hooks_on_receive_and_withdraw() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    let msg = ExecuteMsg::UpdateHooks {
+        add: vec![
+            HookInfo {
+                contract: TEST_HOOK.to_string(),
+                fatal: true,
+            },
+            HookInfo {
+                contract: TEST_HOOK2.to_string(),
+                fatal: false,
+            },
+        ],
+        remove: vec![],
+    };
+    let info = mock_info(TEST_CREATOR, &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let amount = Uint128::from(1000u128);
+    deps.querier.with_token_balances(&[(
+        &TEST_ANCHOR_TOKEN.to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &amount)],
+    )]);
+    deps.querier.with_gov_stakers(&[(
+        &TEST_ANCHOR_GOV.to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &StakerResponse {
+                balance: Uint128::zero(),
+                share: Uint128::zero(),
+                locked_balance: vec![],
+            },
+        )],
+    )]);
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_ALICE.to_string(),
+        amount,
+        msg: to_binary(&Cw20HookMsg::StakingTokens {
+            lock_duration: None,
+        })
+        .unwrap(),
+    });
+    let info = mock_info(TEST_ANCHOR_TOKEN, &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let hook_msg = |user: &str, delta: ShareDelta, shares: u128| {
+        to_binary(&HookMsg::SharesChanged {
+            user: user.to_string(),
+            delta,
+            shares: Uint128::from(shares),
+        })
+        .unwrap()
+    };
+    let fatal = |msg: Binary| {
+        SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: TEST_HOOK.to_string(),
+            msg,
+            funds: vec![],
+        }))
+    };
+    let best_effort = |msg: Binary| {
+        SubMsg::reply_on_error(
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: TEST_HOOK2.to_string(),
+                msg,
+                funds: vec![],
+            }),
+            HOOK_REPLY_ID,
+        )
+    };
+    // dev fee shares are notified before the depositor's
+    let dev_msg = hook_msg(
+        TEST_DEV,
+        ShareDelta::Increase {
+            amount: Uint128::from(20u128),
+        },
+        20,
+    );
+    let alice_msg = hook_msg(
+        TEST_ALICE,
+        ShareDelta::Increase {
+            amount: Uint128::from(980u128),
+        },
+        980,
+    );
+    assert_eq!(
+        res.messages[1..].to_vec(),
+        vec![
+            fatal(dev_msg.clone()),
+            best_effort(dev_msg),
+            fatal(alice_msg.clone()),
+            best_effort(alice_msg),
+        ]
+    );
+
+    deps.querier.with_gov_stakers(&[(
+        &TEST_ANCHOR_GOV.to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &StakerResponse {
+                balance: amount,
+                share: amount,
+                locked_balance: vec![],
+            },
+        )],
+    )]);
+    let msg = ExecuteMsg::WithdrawToken {
+        amount: Some(Uint128::from(480u128)),
+    };
+    let info = mock_info(TEST_ALICE, &[]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    let alice_msg = hook_msg(
+        TEST_ALICE,
+        ShareDelta::Decrease {
+            amount: Uint128::from(480u128),
+        },
+        500,
+    );
+    assert_eq!(
+        res.messages[1..].to_vec(),
+        vec![fatal(alice_msg.clone()), best_effort(alice_msg)]
+    );
+
+    // a failing best-effort hook is caught
+    let reply_msg = Reply {
+        id: HOOK_REPLY_ID,
+        result: ContractResult::Err("hook error".to_string()),
+    };
+    let res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![attr("action", "hook_failed"), attr("error", "hook error")]
+    );
+}
+
+#[test]
+fn query_shares_at_height() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    let info = mock_info(TEST_ANCHOR_TOKEN, &[]);
+    let mut env = mock_env();
+    // each deposit sees the gov balance of the deposits before it
+    for (height, user, amount, gov_balance) in [
+        (100, TEST_ALICE, 1000u128, 0u128),
+        (200, TEST_ALICE, 1000u128, 1000u128),
+        (200, TEST_BOB, 500u128, 2000u128),
+    ]
+    .iter()
+    {
+        env.block.height = *height;
+        deps.querier.with_gov_stakers(&[(
+            &TEST_ANCHOR_GOV.to_string(),
+            &[(
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &StakerResponse {
+                    balance: Uint128::from(*gov_balance),
+                    share: Uint128::from(*gov_balance),
+                    locked_balance: vec![],
+                },
+            )],
+        )]);
+        deps.querier.with_token_balances(&[(
+            &TEST_ANCHOR_TOKEN.to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(*amount))],
+        )]);
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: user.to_string(),
+            amount: Uint128::from(*amount),
+            msg: to_binary(&Cw20HookMsg::StakingTokens {
+                lock_duration: None,
+            })
+            .unwrap(),
+        });
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    }
+    deps.querier.with_gov_stakers(&[(
+        &TEST_ANCHOR_GOV.to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &StakerResponse {
+                balance: Uint128::from(2500u128),
+                share: Uint128::from(2500u128),
+                locked_balance: vec![],
+            },
+        )],
+    )]);
+    env.block.height = 300;
+    let msg = ExecuteMsg::WithdrawToken { amount: None };
+    execute(deps.as_mut(), env, mock_info(TEST_ALICE, &[]), msg).unwrap();
+
+    // snapshots hold the shares at the beginning of the height
+    for (height, shares) in [
+        (100, 0u128),
+        (101, 980u128),
+        (200, 980u128),
+        (201, 1960u128),
+        (300, 1960u128),
+        (301, 0u128),
+        (1000, 0u128),
+    ]
+    .iter()
+    {
+        let msg = QueryMsg::UserStateAtHeight {
+            user: TEST_ALICE.to_string(),
+            height: *height,
+        };
+        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+        let response: UserStateAtHeightResponse = from_binary(&res).unwrap();
+        assert_eq!(
+            response,
+            UserStateAtHeightResponse {
+                user: TEST_ALICE.to_string(),
+                height: *height,
+                shares: Uint128::from(*shares),
+            }
+        );
+    }
+    for (height, total_shares) in [
+        (100, 0u128),
+        (150, 1000u128),
+        (200, 1000u128),
+        (201, 2500u128),
+        (301, 540u128),
+    ]
+    .iter()
+    {
+        let msg = QueryMsg::TotalSharesAtHeight { height: *height };
+        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+        let response: TotalSharesAtHeightResponse = from_binary(&res).unwrap();
+        assert_eq!(response.total_shares, Uint128::from(*total_shares));
+    }
+    let msg = QueryMsg::UserStateAtHeight {
+        user: TEST_BOB.to_string(),
+        height: 1000,
+    };
+    let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+    let response: UserStateAtHeightResponse = from_binary(&res).unwrap();
+    assert_eq!(response.shares, Uint128::from(490u128));
+}
+
+#[test]
+fn proper_merkle_tree() {
+    let holders: Vec<HolderShares> = [
+        (TEST_ALICE, 100u128),
+        (TEST_BOB, 200u128),
+        (TEST_DEV, 300u128),
+    ]
+    .iter()
+    .map(|(address, shares)| HolderShares {
+        address: address.to_string(),
+        shares: Uint128::from(*shares),
+    })
+    .collect();
+    let tree = MerkleTree::new(&holders).unwrap();
+    let root = tree.root();
+    for (i, holder) in holders.iter().enumerate() {
+        let proof = tree.proof(i).unwrap();
+        assert!(merkle::verify(&root, &holder.address, holder.shares, &proof).unwrap());
+        assert!(!merkle::verify(&root, &holder.address, Uint128::from(1u128), &proof).unwrap());
+    }
+    // the odd leaf is carried up, its proof is the root of the others
+    assert_eq!(tree.proof(2).unwrap().len(), 1);
+    assert!(tree.proof(3).is_err());
+    assert!(MerkleTree::new(&[]).is_err());
+}
+
+#[test]
+fn proper_commit_merkle_root() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    for (height, user, shares) in [
+        (100, TEST_ALICE, 100u128),
+        (100, TEST_BOB, 200u128),
+        (200, TEST_ALICE, 0u128),
+        (200, TEST_DEV, 300u128),
+    ]
+    .iter()
+    {
+        let user = deps.api.addr_canonicalize(user).unwrap();
+        save_user_shares(
+            &mut deps.storage,
+            *height,
+            *height * 5,
+            &user,
+            Uint128::from(*shares),
+        )
+        .unwrap();
+    }
+    save_total_shares(&mut deps.storage, 100, 100 * 5, Uint128::from(300u128)).unwrap();
+    save_total_shares(&mut deps.storage, 200, 200 * 5, Uint128::from(500u128)).unwrap();
+
+    // page through the holders at 150, alice exited later but is still listed
+    let mut holders = vec![];
+    let mut start_after = None;
+    loop {
+        let msg = QueryMsg::SharesAtHeight {
+            height: 150,
+            start_after,
+            limit: Some(1),
+        };
+        let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+        let response: SharesAtHeightResponse = from_binary(&res).unwrap();
+        holders.extend(response.holders);
+        start_after = response.last_user;
+        if start_after.is_none() {
+            break;
+        }
+    }
+    assert_eq!(holders.len(), 2);
+    assert!(holders.contains(&HolderShares {
+        address: TEST_ALICE.to_string(),
+        shares: Uint128::from(100u128),
+    }));
+    let tree = MerkleTree::new(&holders).unwrap();
+
+    let mut env = mock_env();
+    env.block.height = 150;
+    let msg = ExecuteMsg::CommitMerkleRoot {
+        height: 150,
+        merkle_root: tree.root(),
+        replace: None,
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_ALICE, &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => {}
+        _ => panic!("Must return Unauthorized error"),
+    }
+    let info = mock_info(TEST_CREATOR, &[]);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        ExecuteMsg::CommitMerkleRoot {
+            height: 151,
+            merkle_root: tree.root(),
+            replace: None,
+        },
+    );
+    match res {
+        Err(ContractError::SnapshotNotFinal {}) => {}
+        _ => panic!("Must return SnapshotNotFinal error"),
+    }
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        ExecuteMsg::CommitMerkleRoot {
+            height: 150,
+            merkle_root: "abcd".to_string(),
+            replace: None,
+        },
+    );
+    match res {
+        Err(ContractError::InvalidMerkleRoot {}) => {}
+        _ => panic!("Must return InvalidMerkleRoot error"),
+    }
+    execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
+
+    // committed roots are only replaced explicitly
+    let res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
+    match res {
+        Err(ContractError::MerkleRootExists {}) => {}
+        _ => panic!("Must return MerkleRootExists error"),
+    }
+    let msg = ExecuteMsg::CommitMerkleRoot {
+        height: 150,
+        merkle_root: tree.root(),
+        replace: Some(true),
+    };
+    let res = execute(deps.as_mut(), env, info, msg).unwrap();
+    assert_eq!(
+        res.events,
+        vec![Event::new("merkle_root_replaced")
+            .add_attribute("height", "150")
+            .add_attribute("old_merkle_root", tree.root())
+            .add_attribute("merkle_root", tree.root())]
+    );
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::MerkleRoot { height: None },
+    )
+    .unwrap();
+    let response: MerkleRootResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        response,
+        MerkleRootResponse {
+            height: 150,
+            merkle_root: tree.root(),
+            total_shares: Uint128::from(300u128),
+        }
+    );
+    let msg = QueryMsg::MerkleRoot { height: Some(150) };
+    let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+    let response: MerkleRootResponse = from_binary(&res).unwrap();
+    let proof = tree.proof(0).unwrap();
+    assert!(merkle::verify(
+        &response.merkle_root,
+        &holders[0].address,
+        holders[0].shares,
+        &proof
+    )
+    .unwrap());
+}
+
+#[test]
+fn proper_airdrop() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    for (user, shares) in [(TEST_ALICE, 300u128), (TEST_BOB, 100u128)].iter() {
+        let user = deps.api.addr_canonicalize(user).unwrap();
+        save_user_shares(
+            &mut deps.storage,
+            100,
+            100 * 5,
+            &user,
+            Uint128::from(*shares),
+        )
+        .unwrap();
+    }
+    save_total_shares(&mut deps.storage, 100, 100 * 5, Uint128::from(400u128)).unwrap();
+    deps.querier.with_token_balances(&[
+        (
+            &TEST_AIRDROP_TOKEN.to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(1000u128))],
+        ),
+        (
+            &TEST_ANCHOR_TOKEN.to_string(),
+            &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(1000u128))],
+        ),
+    ]);
+
+    let mut env = mock_env();
+    env.block.height = 200;
+    let info = mock_info(TEST_CREATOR, &[]);
+    let msg = ExecuteMsg::RegisterAirdrop {
+        token: TEST_AIRDROP_TOKEN.to_string(),
+        amount: Uint128::from(1000u128),
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_ALICE, &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => {}
+        _ => panic!("Must return Unauthorized error"),
+    }
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        ExecuteMsg::RegisterAirdrop {
+            token: TEST_ANCHOR_TOKEN.to_string(),
+            amount: Uint128::from(1000u128),
+        },
+    );
+    match res {
+        Err(ContractError::InvalidAirdropToken {}) => {}
+        _ => panic!("Must return InvalidAirdropToken error"),
+    }
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        ExecuteMsg::RegisterAirdrop {
+            token: TEST_AIRDROP_TOKEN.to_string(),
+            amount: Uint128::from(1001u128),
+        },
+    );
+    match res {
+        Err(ContractError::InsufficientFunds {}) => {}
+        _ => panic!("Must return InsufficientFunds error"),
+    }
+    execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
+    let res = execute(deps.as_mut(), env, info.clone(), msg);
+    match res {
+        Err(ContractError::AirdropExists {}) => {}
+        _ => panic!("Must return AirdropExists error"),
+    }
+
+    // alice exits in the registration block, the snapshot is taken before it
+    let alice = deps.api.addr_canonicalize(TEST_ALICE).unwrap();
+    save_user_shares(&mut deps.storage, 200, 200 * 5, &alice, Uint128::zero()).unwrap();
+    let msg = ExecuteMsg::ClaimAirdrop {
+        token: TEST_AIRDROP_TOKEN.to_string(),
+        height: 200,
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(TEST_ALICE, &[]),
+        msg.clone(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: TEST_AIRDROP_TOKEN.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: TEST_ALICE.to_string(),
+                amount: Uint128::from(750u128),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))]
+    );
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(TEST_ALICE, &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::AlreadyClaimed {}) => {}
+        _ => panic!("Must return AlreadyClaimed error"),
+    }
+    let res = execute(deps.as_mut(), mock_env(), mock_info(TEST_DEV, &[]), msg);
+    match res {
+        Err(ContractError::NothingToClaim {}) => {}
+        _ => panic!("Must return NothingToClaim error"),
+    }
+
+    let msg = QueryMsg::Airdrop {
+        token: TEST_AIRDROP_TOKEN.to_string(),
+        height: 200,
+    };
+    let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+    let airdrop_response: AirdropResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        airdrop_response,
+        AirdropResponse {
+            token: TEST_AIRDROP_TOKEN.to_string(),
+            height: 200,
+            amount: Uint128::from(1000u128),
+            total_shares: Uint128::from(400u128),
+            claimed: Uint128::from(750u128),
+        }
+    );
+    let msg = QueryMsg::AirdropClaim {
+        token: TEST_AIRDROP_TOKEN.to_string(),
+        height: 200,
+        user: TEST_BOB.to_string(),
+    };
+    let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+    let claim_response: AirdropClaimResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        claim_response,
+        AirdropClaimResponse {
+            amount: Uint128::from(250u128),
+            claimed: false,
+        }
+    );
+
+    // the unclaimed part can not be recovered
+    deps.querier.with_token_balances(&[(
+        &TEST_AIRDROP_TOKEN.to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(260u128))],
+    )]);
+    let msg = ExecuteMsg::RecoverToken {
+        token: TokenInfo::Token {
+            contract_addr: TEST_AIRDROP_TOKEN.to_string(),
+        },
+        amount: Uint128::from(11u128),
+        recipient: TEST_CREATOR.to_string(),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info.clone(), msg);
+    match res {
+        Err(ContractError::InsufficientFunds {}) => {}
+        _ => panic!("Must return InsufficientFunds error"),
+    }
+    let msg = ExecuteMsg::RecoverToken {
+        token: TokenInfo::Token {
+            contract_addr: TEST_AIRDROP_TOKEN.to_string(),
+        },
+        amount: Uint128::from(10u128),
+        recipient: TEST_CREATOR.to_string(),
+    };
+    execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+
+    // the token is airdropped again later, earlier unclaimed amounts stay reserved
+    deps.querier.with_token_balances(&[(
+        &TEST_AIRDROP_TOKEN.to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(270u128))],
+    )]);
+    let mut env = mock_env();
+    env.block.height = 300;
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        info.clone(),
+        ExecuteMsg::RegisterAirdrop {
+            token: TEST_AIRDROP_TOKEN.to_string(),
+            amount: Uint128::from(21u128),
+        },
+    );
+    match res {
+        Err(ContractError::InsufficientFunds {}) => {}
+        _ => panic!("Must return InsufficientFunds error"),
+    }
+    let msg = ExecuteMsg::RegisterAirdrop {
+        token: TEST_AIRDROP_TOKEN.to_string(),
+        amount: Uint128::from(20u128),
+    };
+    execute(deps.as_mut(), env, info, msg).unwrap();
+    let msg = QueryMsg::Airdrop {
+        token: TEST_AIRDROP_TOKEN.to_string(),
+        height: 300,
+    };
+    let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+    let airdrop_response: AirdropResponse = from_binary(&res).unwrap();
+    assert_eq!(airdrop_response.amount, Uint128::from(20u128));
+    assert_eq!(airdrop_response.claimed, Uint128::zero());
+
+    let msg = ExecuteMsg::ClaimAirdrop {
+        token: TEST_AIRDROP_TOKEN.to_string(),
+        height: 300,
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info(TEST_BOB, &[]), msg).unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: TEST_AIRDROP_TOKEN.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: TEST_BOB.to_string(),
+                amount: Uint128::from(5u128),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))]
+    );
+    let msg = QueryMsg::AirdropClaim {
+        token: TEST_AIRDROP_TOKEN.to_string(),
+        height: 200,
+        user: TEST_BOB.to_string(),
+    };
+    let res = query(deps.as_ref(), mock_env(), msg).unwrap();
+    let claim_response: AirdropClaimResponse = from_binary(&res).unwrap();
+    assert!(!claim_response.claimed);
+}
+
+#[test]
+fn proper_compound() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(TEST_BOB, &[]),
+        ExecuteMsg::Compound {},
+    );
+    match res {
+        Err(ContractError::NothingStaked {}) => {}
+        _ => panic!("Must return NothingStaked error"),
+    }
+
+    let alice = deps.api.addr_canonicalize(TEST_ALICE).unwrap();
+    save_user_shares(
+        &mut deps.storage,
+        100,
+        100 * 5,
+        &alice,
+        Uint128::from(1000u128),
+    )
+    .unwrap();
+    save_total_shares(&mut deps.storage, 100, 100 * 5, Uint128::from(1000u128)).unwrap();
+    deps.querier.with_token_balances(&[(
+        &TEST_ANCHOR_TOKEN.to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(100u128))],
+    )]);
+    deps.querier.with_gov_stakers(&[(
+        &TEST_ANCHOR_GOV.to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &StakerResponse {
+                balance: Uint128::from(1000u128),
+                share: Uint128::from(1000u128),
+                locked_balance: vec![],
+            },
+        )],
+    )]);
+
+    let msg = ExecuteMsg::UpdateCompoundIncentive {
+        rate: Decimal::percent(1),
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(TEST_ALICE, &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized {}) => {}
+        _ => panic!("Must return Unauthorized error"),
+    }
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(TEST_CREATOR, &[]),
+        ExecuteMsg::UpdateCompoundIncentive {
+            rate: Decimal::percent(6),
+        },
+    );
+    match res {
+        Err(ContractError::CompoundIncentiveOutOfLimits {}) => {}
+        _ => panic!("Must return CompoundIncentiveOutOfLimits error"),
+    }
+    execute(deps.as_mut(), mock_env(), mock_info(TEST_CREATOR, &[]), msg).unwrap();
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(TEST_BOB, &[]),
+        ExecuteMsg::Compound {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: TEST_ANCHOR_TOKEN.to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Send {
+                    contract: TEST_ANCHOR_GOV.to_string(),
+                    amount: Uint128::from(99u128),
+                    msg: to_binary(&GovCw20HookMsg::StakeVotingTokens {}).unwrap(),
+                })
+                .unwrap(),
+                funds: vec![],
+            })),
+            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: TEST_ANCHOR_TOKEN.to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                    recipient: TEST_BOB.to_string(),
+                    amount: Uint128::from(1u128),
+                })
+                .unwrap(),
+                funds: vec![],
+            })),
+        ]
+    );
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::LastCompound {}).unwrap();
+    let compound_response: CompoundResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        compound_response,
+        CompoundResponse {
+            height: mock_env().block.height,
+            time: mock_env().block.time.seconds(),
+            staked: Uint128::from(99u128),
+            incentive: Uint128::from(1u128),
+            exchange_rate_before: Decimal::one(),
+            exchange_rate_after: Decimal::from_ratio(1099u128, 1000u128),
+        }
+    );
+
+    // unsolicited ANC is kept for the owner under Refund
+    let msg = ExecuteMsg::UpdateStrayAncPolicy {
+        policy: StrayAncPolicy::Refund,
+    };
+    execute(deps.as_mut(), mock_env(), mock_info(TEST_CREATOR, &[]), msg).unwrap();
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(TEST_BOB, &[]),
+        ExecuteMsg::Compound {},
+    );
+    match res {
+        Err(ContractError::NothingToCompound {}) => {}
+        _ => panic!("Must return NothingToCompound error"),
+    }
+}
+
+#[test]
+fn proper_compound_with_outstanding_poll_deposit() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    mock_shares(deps.as_mut(), 600, 400);
+    deps.querier.with_gov_stakers(&[(
+        &TEST_ANCHOR_GOV.to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &StakerResponse {
+                balance: Uint128::from(1000u128),
+                share: Uint128::from(1000u128),
+                locked_balance: vec![],
+            },
+        )],
+    )]);
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_CREATOR.to_string(),
+        amount: Uint128::from(100u128),
+        msg: to_binary(&Cw20HookMsg::CreateGovPoll {
+            title: "title".to_string(),
+            description: "description".to_string(),
+            link: None,
+            execute_msgs: None,
+        })
+        .unwrap(),
+    });
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(TEST_ANCHOR_TOKEN, &[]),
+        msg,
+    )
+    .unwrap();
+    let reply_msg = Reply {
+        id: CREATE_POLL_REPLY_ID,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![Event::new("wasm")
+                .add_attribute("action", "create_poll")
+                .add_attribute("poll_id", "7")],
+            data: None,
+        }),
+    };
+    reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+
+    // the deposit refunded by gov is not compounded before its settlement
+    deps.querier.with_token_balances(&[(
+        &TEST_ANCHOR_TOKEN.to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(100u128))],
+    )]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(TEST_BOB, &[]),
+        ExecuteMsg::Compound {},
+    );
+    match res {
+        Err(ContractError::NothingToCompound {}) => {}
+        _ => panic!("Must return NothingToCompound error"),
+    }
+
+    deps.querier.with_token_balances(&[(
+        &TEST_ANCHOR_TOKEN.to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::from(150u128))],
+    )]);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(TEST_BOB, &[]),
+        ExecuteMsg::Compound {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: TEST_ANCHOR_TOKEN.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Send {
+                contract: TEST_ANCHOR_GOV.to_string(),
+                amount: Uint128::from(50u128),
+                msg: to_binary(&GovCw20HookMsg::StakeVotingTokens {}).unwrap(),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))]
+    );
+}
+
+#[test]
+fn query_average_shares() {
+    let mut deps = mock_dependencies(&[]);
+    mock_instantiate(deps.as_mut());
+    let alice = deps.api.addr_canonicalize(TEST_ALICE).unwrap();
+    let bob = deps.api.addr_canonicalize(TEST_BOB).unwrap();
+    for (time, user, shares, total_shares) in [
+        (1000u64, &alice, 100u128, 100u128),
+        (1500u64, &bob, 100u128, 200u128),
+        (2000u64, &alice, 300u128, 400u128),
+        (3000u64, &alice, 0u128, 300u128),
+    ]
+    .iter()
+    {
+        save_user_shares(
+            &mut deps.storage,
+            *time / 10,
+            *time,
+            user,
+            Uint128::from(*shares),
+        )
+        .unwrap();
+        save_total_shares(
+            &mut deps.storage,
+            *time / 10,
+            *time,
+            Uint128::from(*total_shares),
+        )
+        .unwrap();
+    }
+
+    let mut env = mock_env();
+    env.block.time = Timestamp::from_seconds(4000);
+    let msg = QueryMsg::AverageShares {
+        user: TEST_ALICE.to_string(),
+        start: 500,
+        end: 2500,
+    };
+    let res = query(deps.as_ref(), env.clone(), msg).unwrap();
+    let average_response: AverageSharesResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        average_response,
+        AverageSharesResponse {
+            user: TEST_ALICE.to_string(),
+            start: 500,
+            end: 2500,
+            share_seconds: Uint128::from(250_000u128),
+            total_share_seconds: Uint128::from(350_000u128),
+            average_shares: Uint128::from(125u128),
+            average_total_shares: Uint128::from(175u128),
+        }
+    );
+
+    // shares held after the last change accrue up to end
+    let msg = QueryMsg::AverageShares {
+        user: TEST_BOB.to_string(),
+        start: 0,
+        end: 4000,
+    };
+    let res = query(deps.as_ref(), env.clone(), msg).unwrap();
+    let average_response: AverageSharesResponse = from_binary(&res).unwrap();
+    assert_eq!(average_response.share_seconds, Uint128::from(250_000u128));
+    assert_eq!(average_response.average_shares, Uint128::from(62u128));
+    assert_eq!(
+        average_response.total_share_seconds,
+        Uint128::from(850_000u128)
+    );
+
+    for (start, end) in [(2500u64, 2500u64), (3000u64, 4001u64)].iter() {
+        let msg = QueryMsg::AverageShares {
+            user: TEST_ALICE.to_string(),
+            start: *start,
+            end: *end,
+        };
+        match query(deps.as_ref(), env.clone(), msg) {
+            Err(ContractError::InvalidTimeRange {}) => {}
+            _ => panic!("Must return InvalidTimeRange error"),
+        }
+    }
 }